@@ -1,13 +1,20 @@
+use std::collections::HashMap;
 use std::fmt::Display;
 
+use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
+use crate::PriceStrategy;
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "standard")]
 #[serde(rename_all = "snake_case")]
 pub enum NearEvent {
     Nep171(Nep171Event),
+    NearlendToken(NearlendTokenEvent),
+    NearlendAdmin(NearlendAdminEvent),
+    NearlendSeries(NearlendSeriesEvent),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -54,6 +61,337 @@ pub struct NftBurnData {
     pub memo: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NearlendTokenEvent {
+    pub version: String,
+    #[serde(flatten)]
+    pub event_kind: NearlendTokenEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum NearlendTokenEventKind {
+    TokenLock(Vec<TokenLockData>),
+    TokenUnlock(Vec<TokenUnlockData>),
+    TokenUpdateUser(Vec<TokenUpdateUserData>),
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TokenLockData {
+    pub authorized_id: Option<String>,
+    pub owner_id: String,
+    pub locker_id: String,
+    pub token_ids: Vec<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TokenUnlockData {
+    pub owner_id: String,
+    pub locker_id: String,
+    pub token_ids: Vec<String>,
+}
+
+/// `user_id` and `expires` are omitted when the user is cleared
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TokenUpdateUserData {
+    pub authorized_id: Option<String>,
+    pub owner_id: String,
+    pub user_id: Option<String>,
+    pub expires: Option<u32>,
+    pub token_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NearlendAdminEvent {
+    pub version: String,
+    #[serde(flatten)]
+    pub event_kind: NearlendAdminEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum NearlendAdminEventKind {
+    RoleGrant(Vec<RoleData>),
+    RoleRevoke(Vec<RoleData>),
+    PauseUpdate(Vec<PauseUpdateData>),
+    OwnershipPropose(Vec<OwnershipData>),
+    OwnershipCancel(Vec<OwnershipData>),
+    OwnershipAccept(Vec<OwnershipData>),
+    CodeStage(Vec<CodeStageData>),
+    CodeDeploy(Vec<CodeDeployData>),
+    UpgradeTimelockUpdate(Vec<UpgradeTimelockUpdateData>),
+    FeeSchedule(Vec<FeeScheduleData>),
+    FeeScheduleCancel(Vec<FeeScheduleCancelData>),
+    FeeUpdate(Vec<FeeUpdateData>),
+    TreasuryUpdate(Vec<TreasuryUpdateData>),
+    StateMigrate(Vec<StateMigrateData>),
+    ContractMetadataUpdate(Vec<ContractMetadataUpdateData>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RoleData {
+    pub authorized_id: String,
+    pub role: String,
+    pub account_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PauseUpdateData {
+    pub authorized_id: String,
+    pub operation: String,
+    pub paused: bool,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OwnershipData {
+    pub old_owner_id: String,
+    pub new_owner_id: String,
+    pub expires: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CodeStageData {
+    pub authorized_id: String,
+    pub code_hash: String,
+    pub activation_time: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CodeDeployData {
+    pub authorized_id: String,
+    pub code_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpgradeTimelockUpdateData {
+    pub authorized_id: String,
+    pub old_upgrade_timelock: u32,
+    pub new_upgrade_timelock: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FeeScheduleData {
+    pub authorized_id: String,
+    pub current_fee: u16,
+    pub next_fee: u16,
+    pub start_time: u32,
+}
+
+/// Scheduled fee dropped before its start time by a later `set_transaction_fee`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FeeScheduleCancelData {
+    pub authorized_id: String,
+    pub next_fee: u16,
+    pub start_time: u32,
+}
+
+/// `authorized_id` is omitted when a scheduled fee is applied at its start time
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FeeUpdateData {
+    pub authorized_id: Option<String>,
+    pub old_fee: u16,
+    pub new_fee: u16,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TreasuryUpdateData {
+    pub authorized_id: String,
+    pub old_treasury_id: String,
+    pub new_treasury_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StateMigrateData {
+    pub authorized_id: String,
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContractMetadataUpdateData {
+    pub authorized_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NearlendSeriesEvent {
+    pub version: String,
+    #[serde(flatten)]
+    pub event_kind: NearlendSeriesEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum NearlendSeriesEventKind {
+    SeriesCreate(Vec<SeriesCreateData>),
+    SeriesSetPrice(Vec<SeriesSetPriceData>),
+    SeriesSetNonMintable(Vec<SeriesData>),
+    SeriesDecreaseCopies(Vec<SeriesDecreaseCopiesData>),
+    SeriesSetSaleWindow(Vec<SeriesSetSaleWindowData>),
+    SeriesSetMaxPerAccount(Vec<SeriesSetMaxPerAccountData>),
+    SeriesSetPriceStrategy(Vec<SeriesSetPriceStrategyData>),
+    SeriesSetPresale(Vec<SeriesSetPresaleData>),
+    SeriesAddPresaleAllowlist(Vec<SeriesAddPresaleAllowlistData>),
+    SeriesRemovePresaleAllowlist(Vec<SeriesRemovePresaleAllowlistData>),
+    AuctionCreate(Vec<AuctionCreateData>),
+    AuctionBid(Vec<AuctionBidData>),
+    AuctionSettle(Vec<AuctionSettleData>),
+    AuctionCancel(Vec<SeriesData>),
+    SeriesSetRevealCommitment(Vec<SeriesSetRevealCommitmentData>),
+    SeriesReveal(Vec<SeriesRevealData>),
+    SeriesSetRandomEditions(Vec<SeriesSetRandomEditionsData>),
+    SeriesUpdateMetadata(Vec<SeriesUpdateMetadataData>),
+    SeriesFreezeMetadata(Vec<SeriesData>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesData {
+    pub token_series_id: String,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesCreateData {
+    pub token_series_id: String,
+    pub creator_id: String,
+    pub token_metadata: TokenMetadata,
+    pub price: Option<String>,
+    pub royalty: HashMap<String, u32>,
+    pub transaction_fee: String,
+    pub ft_token_id: Option<String>,
+    pub sale_start: Option<u32>,
+    pub sale_end: Option<u32>,
+    pub max_per_account: Option<u32>,
+}
+
+/// `price` is omitted when the series is set not for sale
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSetPriceData {
+    pub token_series_id: String,
+    pub price: Option<String>,
+    pub transaction_fee: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesDecreaseCopiesData {
+    pub token_series_id: String,
+    pub copies: u64,
+    pub is_non_mintable: bool,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSetSaleWindowData {
+    pub token_series_id: String,
+    pub sale_start: Option<u32>,
+    pub sale_end: Option<u32>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSetMaxPerAccountData {
+    pub token_series_id: String,
+    pub max_per_account: Option<u32>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSetPriceStrategyData {
+    pub token_series_id: String,
+    pub price_strategy: Option<PriceStrategy>,
+    /// `None` in the logs written before the strategy refreshed the series transaction fee
+    pub transaction_fee: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSetPresaleData {
+    pub token_series_id: String,
+    pub public_sale_start: Option<u32>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PresaleAllowlistData {
+    pub account_id: String,
+    pub quota: u32,
+    pub price: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesAddPresaleAllowlistData {
+    pub token_series_id: String,
+    pub allowlist: Vec<PresaleAllowlistData>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesRemovePresaleAllowlistData {
+    pub token_series_id: String,
+    pub account_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuctionCreateData {
+    pub token_series_id: String,
+    pub reserve_price: String,
+    pub min_bid_increment: String,
+    pub end_time: u32,
+    pub extension: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuctionBidData {
+    pub token_series_id: String,
+    pub bidder_id: String,
+    pub bid: String,
+    pub end_time: u32,
+}
+
+/// `token_id` is omitted when the auction ended without bids
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuctionSettleData {
+    pub token_series_id: String,
+    pub token_id: Option<String>,
+}
+
+/// `commitment` is the base58 sha256 of the manifest, or of `{cid}:{seed}`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSetRevealCommitmentData {
+    pub token_series_id: String,
+    pub commitment: String,
+}
+
+/// `cid` and `seed` are omitted when the full manifest was revealed
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesRevealData {
+    pub token_series_id: String,
+    pub cid: Option<String>,
+    pub seed: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSetRandomEditionsData {
+    pub token_series_id: String,
+    pub is_random: bool,
+}
+
+/// `token_metadata` is the series metadata after the update
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesUpdateMetadataData {
+    pub token_series_id: String,
+    pub token_metadata: TokenMetadata,
+}
+
 impl Display for NearEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&format!("EVENT_JSON:{}", self.to_json_string()))
@@ -80,12 +418,108 @@ impl NearEvent {
         NearEvent::new_171_v1(Nep171EventKind::NftMint(data))
     }
 
+    pub fn new_nearlend_token(version: String, event_kind: NearlendTokenEventKind) -> Self {
+        NearEvent::NearlendToken(NearlendTokenEvent { version, event_kind })
+    }
+
+    pub fn new_nearlend_token_v1(event_kind: NearlendTokenEventKind) -> Self {
+        NearEvent::new_nearlend_token("1.0.0".to_string(), event_kind)
+    }
+
+    pub fn token_lock(data: Vec<TokenLockData>) -> Self {
+        NearEvent::new_nearlend_token_v1(NearlendTokenEventKind::TokenLock(data))
+    }
+
+    pub fn token_unlock(data: Vec<TokenUnlockData>) -> Self {
+        NearEvent::new_nearlend_token_v1(NearlendTokenEventKind::TokenUnlock(data))
+    }
+
+    pub fn token_update_user(data: Vec<TokenUpdateUserData>) -> Self {
+        NearEvent::new_nearlend_token_v1(NearlendTokenEventKind::TokenUpdateUser(data))
+    }
+
+    pub fn new_nearlend_admin(version: String, event_kind: NearlendAdminEventKind) -> Self {
+        NearEvent::NearlendAdmin(NearlendAdminEvent { version, event_kind })
+    }
+
+    pub fn new_nearlend_admin_v1(event_kind: NearlendAdminEventKind) -> Self {
+        NearEvent::new_nearlend_admin("1.0.0".to_string(), event_kind)
+    }
+
+    pub fn role_grant(data: Vec<RoleData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::RoleGrant(data))
+    }
+
+    pub fn role_revoke(data: Vec<RoleData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::RoleRevoke(data))
+    }
+
+    pub fn pause_update(data: Vec<PauseUpdateData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::PauseUpdate(data))
+    }
+
+    pub fn ownership_propose(data: Vec<OwnershipData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::OwnershipPropose(data))
+    }
+
+    pub fn ownership_cancel(data: Vec<OwnershipData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::OwnershipCancel(data))
+    }
+
+    pub fn ownership_accept(data: Vec<OwnershipData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::OwnershipAccept(data))
+    }
+
+    pub fn code_stage(data: Vec<CodeStageData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::CodeStage(data))
+    }
+
+    pub fn code_deploy(data: Vec<CodeDeployData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::CodeDeploy(data))
+    }
+
+    pub fn upgrade_timelock_update(data: Vec<UpgradeTimelockUpdateData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::UpgradeTimelockUpdate(data))
+    }
+
+    pub fn fee_schedule(data: Vec<FeeScheduleData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::FeeSchedule(data))
+    }
+
+    pub fn fee_schedule_cancel(data: Vec<FeeScheduleCancelData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::FeeScheduleCancel(data))
+    }
+
+    pub fn fee_update(data: Vec<FeeUpdateData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::FeeUpdate(data))
+    }
+
+    pub fn treasury_update(data: Vec<TreasuryUpdateData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::TreasuryUpdate(data))
+    }
+
+    pub fn state_migrate(data: Vec<StateMigrateData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::StateMigrate(data))
+    }
+
+    pub fn contract_metadata_update(data: Vec<ContractMetadataUpdateData>) -> Self {
+        NearEvent::new_nearlend_admin_v1(NearlendAdminEventKind::ContractMetadataUpdate(data))
+    }
+
+    pub fn new_nearlend_series(version: String, event_kind: NearlendSeriesEventKind) -> Self {
+        NearEvent::NearlendSeries(NearlendSeriesEvent { version, event_kind })
+    }
+
+    pub fn new_nearlend_series_v1(event_kind: NearlendSeriesEventKind) -> Self {
+        NearEvent::new_nearlend_series("1.0.0".to_string(), event_kind)
+    }
+
     pub(crate) fn to_json_string(&self) -> String {
         serde_json::to_string(self).unwrap()
     }
 
     pub fn log(&self) {
-        near_sdk::env::log(&self.to_string().as_bytes());
+        near_sdk::env::log(self.to_string().as_bytes());
     }
 
     pub fn log_nft_mint(owner_id: String, token_ids: Vec<String>, memo: Option<String>) {
@@ -128,6 +562,160 @@ impl NearEvent {
     pub fn log_nft_burns(data: Vec<NftBurnData>) {
         NearEvent::nft_burn(data).log();
     }
+
+    pub fn log_token_lock(
+        owner_id: String,
+        locker_id: String,
+        token_ids: Vec<String>,
+        reason: Option<String>,
+        authorized_id: Option<String>,
+    ) {
+        NearEvent::token_lock(vec![TokenLockData {
+            authorized_id,
+            owner_id,
+            locker_id,
+            token_ids,
+            reason,
+        }])
+        .log();
+    }
+
+    pub fn log_token_unlock(owner_id: String, locker_id: String, token_ids: Vec<String>) {
+        NearEvent::token_unlock(vec![TokenUnlockData { owner_id, locker_id, token_ids }]).log();
+    }
+
+    pub fn log_token_update_user(
+        owner_id: String,
+        user_id: Option<String>,
+        expires: Option<u32>,
+        token_ids: Vec<String>,
+        authorized_id: Option<String>,
+    ) {
+        NearEvent::token_update_user(vec![TokenUpdateUserData {
+            authorized_id,
+            owner_id,
+            user_id,
+            expires,
+            token_ids,
+        }])
+        .log();
+    }
+
+    pub fn log_nearlend_series(event_kind: NearlendSeriesEventKind) {
+        NearEvent::new_nearlend_series_v1(event_kind).log();
+    }
+
+    pub fn log_role_grant(authorized_id: String, role: String, account_ids: Vec<String>) {
+        NearEvent::role_grant(vec![RoleData { authorized_id, role, account_ids }]).log();
+    }
+
+    pub fn log_role_revoke(authorized_id: String, role: String, account_ids: Vec<String>) {
+        NearEvent::role_revoke(vec![RoleData { authorized_id, role, account_ids }]).log();
+    }
+
+    pub fn log_pause_update(authorized_id: String, operation: String, paused: bool) {
+        NearEvent::pause_update(vec![PauseUpdateData { authorized_id, operation, paused }]).log();
+    }
+
+    pub fn log_ownership_propose(old_owner_id: String, new_owner_id: String, expires: Option<u32>) {
+        NearEvent::ownership_propose(vec![OwnershipData { old_owner_id, new_owner_id, expires }])
+            .log();
+    }
+
+    pub fn log_ownership_cancel(old_owner_id: String, new_owner_id: String) {
+        NearEvent::ownership_cancel(vec![OwnershipData {
+            old_owner_id,
+            new_owner_id,
+            expires: None,
+        }])
+        .log();
+    }
+
+    pub fn log_ownership_accept(old_owner_id: String, new_owner_id: String) {
+        NearEvent::ownership_accept(vec![OwnershipData {
+            old_owner_id,
+            new_owner_id,
+            expires: None,
+        }])
+        .log();
+    }
+
+    pub fn log_code_stage(authorized_id: String, code_hash: String, activation_time: u32) {
+        NearEvent::code_stage(vec![CodeStageData { authorized_id, code_hash, activation_time }])
+            .log();
+    }
+
+    pub fn log_code_deploy(authorized_id: String, code_hash: String) {
+        NearEvent::code_deploy(vec![CodeDeployData { authorized_id, code_hash }]).log();
+    }
+
+    pub fn log_upgrade_timelock_update(
+        authorized_id: String,
+        old_upgrade_timelock: u32,
+        new_upgrade_timelock: u32,
+    ) {
+        NearEvent::upgrade_timelock_update(vec![UpgradeTimelockUpdateData {
+            authorized_id,
+            old_upgrade_timelock,
+            new_upgrade_timelock,
+        }])
+        .log();
+    }
+
+    pub fn log_fee_schedule(
+        authorized_id: String,
+        current_fee: u16,
+        next_fee: u16,
+        start_time: u32,
+    ) {
+        NearEvent::fee_schedule(vec![FeeScheduleData {
+            authorized_id,
+            current_fee,
+            next_fee,
+            start_time,
+        }])
+        .log();
+    }
+
+    pub fn log_fee_schedule_cancel(authorized_id: String, next_fee: u16, start_time: u32) {
+        NearEvent::fee_schedule_cancel(vec![FeeScheduleCancelData {
+            authorized_id,
+            next_fee,
+            start_time,
+        }])
+        .log();
+    }
+
+    pub fn log_fee_update(authorized_id: Option<String>, old_fee: u16, new_fee: u16) {
+        NearEvent::fee_update(vec![FeeUpdateData { authorized_id, old_fee, new_fee }]).log();
+    }
+
+    pub fn log_treasury_update(
+        authorized_id: String,
+        old_treasury_id: String,
+        new_treasury_id: String,
+    ) {
+        NearEvent::treasury_update(vec![TreasuryUpdateData {
+            authorized_id,
+            old_treasury_id,
+            new_treasury_id,
+        }])
+        .log();
+    }
+
+    pub fn log_state_migrate(authorized_id: String, from_version: u32, to_version: u32) {
+        NearEvent::state_migrate(vec![StateMigrateData {
+            authorized_id,
+            from_version,
+            to_version,
+        }])
+        .log();
+    }
+
+    pub fn log_contract_metadata_update(authorized_id: String) {
+        NearEvent::contract_metadata_update(vec![ContractMetadataUpdateData { authorized_id }])
+            .log();
+    }
 }
 
 #[cfg(test)]
@@ -252,4 +840,296 @@ mod tests {
             r#"{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"authorized_id":"4","old_owner_id":"alice","new_owner_id":"bob","token_ids":["2","3"],"memo":"has memo"},{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["0","1"]}]}"#
         );
     }
+
+    #[test]
+    fn token_lock() {
+        let log = NearEvent::token_lock(vec![TokenLockData {
+            authorized_id: Some("lender".to_string()),
+            owner_id: "bob".to_string(),
+            locker_id: "lender".to_string(),
+            token_ids: make_tokens(vec!["1:1"]),
+            reason: Some("loan 42".to_string()),
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_token","version":"1.0.0","event":"token_lock","data":[{"authorized_id":"lender","owner_id":"bob","locker_id":"lender","token_ids":["1:1"],"reason":"loan 42"}]}"#
+        );
+    }
+
+    #[test]
+    fn token_unlock() {
+        let log = NearEvent::token_unlock(vec![TokenUnlockData {
+            owner_id: "bob".to_string(),
+            locker_id: "lender".to_string(),
+            token_ids: make_tokens(vec!["1:1"]),
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_token","version":"1.0.0","event":"token_unlock","data":[{"owner_id":"bob","locker_id":"lender","token_ids":["1:1"]}]}"#
+        );
+    }
+
+    #[test]
+    fn token_update_user() {
+        let log = NearEvent::token_update_user(vec![
+            TokenUpdateUserData {
+                authorized_id: None,
+                owner_id: "bob".to_string(),
+                user_id: Some("alice".to_string()),
+                expires: Some(1_700_000_000),
+                token_ids: make_tokens(vec!["1:1"]),
+            },
+            TokenUpdateUserData {
+                authorized_id: None,
+                owner_id: "bob".to_string(),
+                user_id: None,
+                expires: None,
+                token_ids: make_tokens(vec!["1:2"]),
+            },
+        ])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_token","version":"1.0.0","event":"token_update_user","data":[{"owner_id":"bob","user_id":"alice","expires":1700000000,"token_ids":["1:1"]},{"owner_id":"bob","token_ids":["1:2"]}]}"#
+        );
+    }
+
+    #[test]
+    fn role_grant() {
+        let log = NearEvent::role_grant(vec![RoleData {
+            authorized_id: "owner".to_string(),
+            role: "fee_manager".to_string(),
+            account_ids: vec!["ops".to_string()],
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"role_grant","data":[{"authorized_id":"owner","role":"fee_manager","account_ids":["ops"]}]}"#
+        );
+    }
+
+    #[test]
+    fn role_revoke() {
+        let log = NearEvent::role_revoke(vec![RoleData {
+            authorized_id: "owner".to_string(),
+            role: "pauser".to_string(),
+            account_ids: vec!["ops".to_string()],
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"role_revoke","data":[{"authorized_id":"owner","role":"pauser","account_ids":["ops"]}]}"#
+        );
+    }
+
+    #[test]
+    fn pause_update() {
+        let log = NearEvent::pause_update(vec![PauseUpdateData {
+            authorized_id: "ops".to_string(),
+            operation: "transfer".to_string(),
+            paused: true,
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"pause_update","data":[{"authorized_id":"ops","operation":"transfer","paused":true}]}"#
+        );
+    }
+
+    #[test]
+    fn ownership_propose() {
+        let log = NearEvent::ownership_propose(vec![OwnershipData {
+            old_owner_id: "owner".to_string(),
+            new_owner_id: "dao".to_string(),
+            expires: Some(1_700_000_000),
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"ownership_propose","data":[{"old_owner_id":"owner","new_owner_id":"dao","expires":1700000000}]}"#
+        );
+    }
+
+    #[test]
+    fn ownership_accept() {
+        let log = NearEvent::ownership_accept(vec![OwnershipData {
+            old_owner_id: "owner".to_string(),
+            new_owner_id: "dao".to_string(),
+            expires: None,
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"ownership_accept","data":[{"old_owner_id":"owner","new_owner_id":"dao"}]}"#
+        );
+    }
+
+    #[test]
+    fn code_stage() {
+        let log = NearEvent::code_stage(vec![CodeStageData {
+            authorized_id: "owner".to_string(),
+            code_hash: "6h7jYGmWEPLDGRMDa6DbUhsR3wAnmYT4krnMCAhFMrdc".to_string(),
+            activation_time: 1_700_000_000,
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"code_stage","data":[{"authorized_id":"owner","code_hash":"6h7jYGmWEPLDGRMDa6DbUhsR3wAnmYT4krnMCAhFMrdc","activation_time":1700000000}]}"#
+        );
+    }
+
+    #[test]
+    fn series_set_price() {
+        let log = NearEvent::new_nearlend_series_v1(NearlendSeriesEventKind::SeriesSetPrice(vec![
+            SeriesSetPriceData {
+                token_series_id: "1".to_string(),
+                price: Some("1000000000000000000000000".to_string()),
+                transaction_fee: "500".to_string(),
+            },
+            SeriesSetPriceData {
+                token_series_id: "2".to_string(),
+                price: None,
+                transaction_fee: "500".to_string(),
+            },
+        ]))
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_series","version":"1.0.0","event":"series_set_price","data":[{"token_series_id":"1","price":"1000000000000000000000000","transaction_fee":"500"},{"token_series_id":"2","transaction_fee":"500"}]}"#
+        );
+    }
+
+    #[test]
+    fn series_decrease_copies() {
+        let log =
+            NearEvent::new_nearlend_series_v1(NearlendSeriesEventKind::SeriesDecreaseCopies(vec![
+                SeriesDecreaseCopiesData {
+                    token_series_id: "1".to_string(),
+                    copies: 5,
+                    is_non_mintable: true,
+                },
+            ]))
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_series","version":"1.0.0","event":"series_decrease_copies","data":[{"token_series_id":"1","copies":5,"is_non_mintable":true}]}"#
+        );
+    }
+
+    #[test]
+    fn auction_settle() {
+        let log = NearEvent::new_nearlend_series_v1(NearlendSeriesEventKind::AuctionSettle(vec![
+            AuctionSettleData {
+                token_series_id: "1".to_string(),
+                token_id: Some("1:1".to_string()),
+            },
+        ]))
+        .to_string();
+        assert_eq!(
+            log,
+            r#"EVENT_JSON:{"standard":"nearlend_series","version":"1.0.0","event":"auction_settle","data":[{"token_series_id":"1","token_id":"1:1"}]}"#
+        );
+    }
+
+    #[test]
+    fn fee_update() {
+        let log = NearEvent::fee_update(vec![
+            FeeUpdateData { authorized_id: Some("ops".to_string()), old_fee: 500, new_fee: 300 },
+            FeeUpdateData { authorized_id: None, old_fee: 300, new_fee: 250 },
+        ])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"fee_update","data":[{"authorized_id":"ops","old_fee":500,"new_fee":300},{"old_fee":300,"new_fee":250}]}"#
+        );
+    }
+
+    #[test]
+    fn fee_schedule_cancel() {
+        let log = NearEvent::fee_schedule_cancel(vec![FeeScheduleCancelData {
+            authorized_id: "ops".to_string(),
+            next_fee: 300,
+            start_time: 1_618_109_122,
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"fee_schedule_cancel","data":[{"authorized_id":"ops","next_fee":300,"start_time":1618109122}]}"#
+        );
+    }
+
+    #[test]
+    fn upgrade_timelock_update() {
+        let log = NearEvent::upgrade_timelock_update(vec![UpgradeTimelockUpdateData {
+            authorized_id: "owner".to_string(),
+            old_upgrade_timelock: 86400,
+            new_upgrade_timelock: 172800,
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"upgrade_timelock_update","data":[{"authorized_id":"owner","old_upgrade_timelock":86400,"new_upgrade_timelock":172800}]}"#
+        );
+    }
+
+    #[test]
+    fn treasury_update() {
+        let log = NearEvent::treasury_update(vec![TreasuryUpdateData {
+            authorized_id: "owner".to_string(),
+            old_treasury_id: "treasury".to_string(),
+            new_treasury_id: "dao".to_string(),
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"treasury_update","data":[{"authorized_id":"owner","old_treasury_id":"treasury","new_treasury_id":"dao"}]}"#
+        );
+    }
+
+    #[test]
+    fn state_migrate() {
+        let log = NearEvent::state_migrate(vec![StateMigrateData {
+            authorized_id: "owner".to_string(),
+            from_version: 2,
+            to_version: 3,
+        }])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_admin","version":"1.0.0","event":"state_migrate","data":[{"authorized_id":"owner","from_version":2,"to_version":3}]}"#
+        );
+    }
+
+    #[test]
+    fn series_reveal() {
+        let log = NearEvent::new_nearlend_series_v1(NearlendSeriesEventKind::SeriesReveal(vec![
+            SeriesRevealData {
+                token_series_id: "1".to_string(),
+                cid: Some(
+                    "bafybeidzcan4nzcz7sczs4yzyxly4galgygnbjewipj6haco4kffoqpkiy".to_string(),
+                ),
+                seed: Some("42".to_string()),
+            },
+            SeriesRevealData { token_series_id: "2".to_string(), cid: None, seed: None },
+        ]))
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nearlend_series","version":"1.0.0","event":"series_reveal","data":[{"token_series_id":"1","cid":"bafybeidzcan4nzcz7sczs4yzyxly4galgygnbjewipj6haco4kffoqpkiy","seed":"42"},{"token_series_id":"2"}]}"#
+        );
+    }
+
+    #[test]
+    fn contract_metadata_update() {
+        let log = NearEvent::contract_metadata_update(vec![ContractMetadataUpdateData {
+            authorized_id: "owner".to_string(),
+        }])
+        .to_string();
+        assert_eq!(
+            log,
+            r#"EVENT_JSON:{"standard":"nearlend_admin","version":"1.0.0","event":"contract_metadata_update","data":[{"authorized_id":"owner"}]}"#
+        );
+    }
 }
\ No newline at end of file
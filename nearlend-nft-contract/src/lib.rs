@@ -1,25 +1,39 @@
-use near_contract_standards::non_fungible_token::core::{
-    NonFungibleTokenCore, NonFungibleTokenResolver,
-};
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenResolver;
 use near_contract_standards::non_fungible_token::metadata::{
     NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
 };
 use near_contract_standards::non_fungible_token::NonFungibleToken;
-use near_contract_standards::non_fungible_token::{Token, TokenId};
+use near_contract_standards::non_fungible_token::TokenId;
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, UnorderedMap, UnorderedSet};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
 use near_sdk::env::is_valid_account_id;
-use near_sdk::json_types::{ValidAccountId, U128, U64};
+use near_sdk::json_types::{Base58CryptoHash, Base64VecU8, ValidAccountId, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     assert_one_yocto, env, ext_contract, near_bindgen, serde_json::json, AccountId, Balance,
-    BorshStorageKey, Gas, PanicOnDefault, Promise, PromiseOrValue, Timestamp,
+    BorshStorageKey, Gas, PanicOnDefault, Promise, PromiseOrValue, PromiseResult, StorageUsage,
+    Timestamp,
 };
 use std::collections::HashMap;
+use std::convert::TryInto;
 
 pub mod event;
+mod migration;
 
 pub use event::NearEvent;
+use event::{
+    AuctionBidData, AuctionCreateData, AuctionSettleData, NearlendSeriesEventKind, NftMintData,
+    PresaleAllowlistData, SeriesAddPresaleAllowlistData, SeriesCreateData, SeriesData,
+    SeriesDecreaseCopiesData, SeriesRemovePresaleAllowlistData, SeriesRevealData,
+    SeriesSetMaxPerAccountData, SeriesSetPresaleData, SeriesSetPriceData,
+    SeriesSetPriceStrategyData, SeriesSetRandomEditionsData, SeriesSetRevealCommitmentData,
+    SeriesSetSaleWindowData, SeriesUpdateMetadataData,
+};
 
 /// between token_series_id and edition number e.g. 42:2 where 42 is series and 2 is edition
 pub const TOKEN_DELIMETER: char = ':';
@@ -32,12 +46,34 @@ const GAS_FOR_RESOLVE_TRANSFER: Gas = 10_000_000_000_000;
 const GAS_FOR_NFT_TRANSFER_CALL: Gas = 30_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER;
 const GAS_FOR_NFT_APPROVE: Gas = 10_000_000_000_000;
 const GAS_FOR_MINT: Gas = 90_000_000_000_000;
+const GAS_FOR_FT_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_FT_PAYOUT: Gas = 5_000_000_000_000;
+const GAS_FOR_DEPLOY_STAGED_CODE: Gas = 20_000_000_000_000;
+/// least gas left for `migrate` on the deployed code
+const MIN_GAS_FOR_MIGRATE: Gas = 50_000_000_000_000;
+/// gas for each token of nft_mint_batch
+const GAS_FOR_BATCH_MINT_TOKEN: Gas = 8_000_000_000_000;
+/// gas kept by nft_mint_batch to charge storage and log the mints
+const GAS_FOR_BATCH_MINT_FINISH: Gas = 10_000_000_000_000;
 const NO_DEPOSIT: Balance = 0;
+const ONE_YOCTO: Balance = 1;
 const MAX_PRICE: Balance = 1_000_000_000 * 10u128.pow(24);
+/// Most prices returned by `nft_get_series_price_quote`
+const MAX_PRICE_QUOTE: u64 = 100;
+/// seconds between staging new code and being able to deploy it
+const DEFAULT_UPGRADE_TIMELOCK: TimestampSec = 86_400;
+/// lowest timelock the owner can set, leaves time to react to staged code
+const MIN_UPGRADE_TIMELOCK: TimestampSec = 3_600;
+/// storage balance entry: key prefix, account id up to 64 bytes, balance and the record overhead
+const STORAGE_BALANCE_BYTES: StorageUsage = 128;
 
 pub type TokenSeriesId = String;
 pub type TimestampSec = u32;
 pub type ContractAndTokenId = String;
+/// e.g. 42:alice.near where 42 is series
+pub type TokenSeriesAndAccountId = String;
+/// e.g. usdc.near:alice.near where usdc.near is the fungible token
+pub type FtTokenAndAccountId = String;
 
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -68,6 +104,11 @@ pub trait NonFungibleTokenReceiver {
     );
 }
 
+#[ext_contract(ext_fungible_token)]
+trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
 #[ext_contract(ext_self)]
 trait NonFungibleTokenResolver {
     fn nft_resolve_transfer(
@@ -77,6 +118,8 @@ trait NonFungibleTokenResolver {
         token_id: TokenId,
         approved_account_ids: Option<HashMap<AccountId, u64>>,
     ) -> bool;
+
+    fn ft_resolve_payout(&mut self, ft_token_id: AccountId, receiver_id: AccountId, amount: U128);
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -87,6 +130,301 @@ pub struct TokenSeries {
     price: Option<Balance>,
     is_mintable: bool,
     royalty: HashMap<AccountId, u32>,
+    /// NEP-141 token the price is denominated in, None means NEAR
+    ft_token_id: Option<AccountId>,
+    /// before this time only allowlisted accounts can buy, None means no presale
+    public_sale_start: Option<TimestampSec>,
+    sale_start: Option<TimestampSec>,
+    sale_end: Option<TimestampSec>,
+    /// maximum tokens a single buyer can get through nft_buy
+    max_per_account: Option<u32>,
+    /// takes precedence over `price` when set
+    price_strategy: Option<PriceStrategy>,
+    /// editions burned and removed from `tokens`, still counted for edition numbers and copies
+    burned: u64,
+    /// hidden final metadata, the series metadata is the placeholder until revealed
+    reveal: Option<SeriesReveal>,
+    /// set when editions are drawn at random, holds the swapped slots of the virtual array
+    /// of unminted editions, a missing slot `i` holds edition `i + 1`
+    unminted_editions: Option<LookupMap<u64, u64>>,
+    /// set once by the creator, the metadata can't be updated afterwards
+    is_metadata_frozen: bool,
+}
+
+impl TokenSeries {
+    /// Editions minted so far, including the ones burned and removed from `tokens`
+    fn minted(&self) -> u64 {
+        self.tokens.len() + self.burned
+    }
+
+    fn is_in_sale_window(&self, now: TimestampSec) -> bool {
+        self.sale_start.is_none_or(|start| now >= start)
+            && self.sale_end.is_none_or(|end| now < end)
+    }
+
+    fn is_edition_minted(&self, edition: u64, token_id: &TokenId) -> bool {
+        match self.unminted_editions {
+            Some(_) => self.tokens.contains(token_id),
+            None => edition <= self.minted(),
+        }
+    }
+
+    /// Next edition number, drawn from the unminted editions with swap-and-pop in random mode
+    fn next_edition(&mut self) -> u64 {
+        let minted = self.minted();
+        let unminted_editions = match self.unminted_editions.as_mut() {
+            Some(unminted_editions) => unminted_editions,
+            None => return minted + 1,
+        };
+
+        let remaining = self.metadata.copies.unwrap() - minted;
+        let seed = env::sha256(&[env::random_seed(), minted.to_le_bytes().to_vec()].concat());
+        let slot = u64::from_le_bytes(seed[..8].try_into().unwrap()) % remaining;
+        let last_slot = remaining - 1;
+
+        let edition = unminted_editions.get(&slot).unwrap_or(slot) + 1;
+        let last_edition = unminted_editions.remove(&last_slot).unwrap_or(last_slot);
+        if slot != last_slot {
+            unminted_editions.insert(&slot, &last_edition);
+        }
+        edition
+    }
+
+    fn current_price(&self, now: TimestampSec) -> Option<Balance> {
+        match &self.price_strategy {
+            Some(price_strategy) => Some(price_strategy.price_at(now, self.minted())),
+            None => self.price,
+        }
+    }
+
+    fn into_json(
+        self,
+        token_series_id: TokenSeriesId,
+        transaction_fee: Option<U128>,
+    ) -> TokenSeriesJson {
+        let is_sale_open = self.is_mintable
+            && (self.price.is_some() || self.price_strategy.is_some())
+            && self.is_in_sale_window(to_sec(env::block_timestamp()));
+        TokenSeriesJson {
+            token_series_id,
+            metadata: self.metadata,
+            creator_id: self.creator_id,
+            royalty: self.royalty,
+            transaction_fee,
+            ft_token_id: self.ft_token_id,
+            public_sale_start: self.public_sale_start,
+            sale_start: self.sale_start,
+            sale_end: self.sale_end,
+            is_sale_open,
+            max_per_account: self.max_per_account,
+            price_strategy: self.price_strategy,
+            reveal_commitment: self
+                .reveal
+                .as_ref()
+                .map(|reveal| Base58CryptoHash::from(reveal.commitment)),
+            is_revealed: self.reveal.is_some_and(|reveal| reveal.revealed.is_some()),
+            is_random_editions: self.unminted_editions.is_some(),
+            is_metadata_frozen: self.is_metadata_frozen,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SeriesReveal {
+    /// sha256 of the manifest JSON, or of `{cid}:{seed}`
+    commitment: [u8; 32],
+    revealed: Option<RevealedMetadata>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum RevealedMetadata {
+    /// the manifest was written to the edition metadata
+    Manifest,
+    /// edition N points to `{cid}/{index}.json`, index is N shifted by `offset` and wrapped at `editions`
+    Cid {
+        cid: String,
+        offset: u64,
+        editions: u64,
+    },
+}
+
+impl RevealedMetadata {
+    fn reference(&self, edition: u64) -> Option<String> {
+        match self {
+            RevealedMetadata::Manifest => None,
+            RevealedMetadata::Cid {
+                cid,
+                offset,
+                editions,
+            } => Some(format!(
+                "{}/{}.json",
+                cid,
+                (edition - 1 + offset) % editions + 1
+            )),
+        }
+    }
+}
+
+/// Price schedule used instead of the fixed series price
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceStrategy {
+    /// starts at start_price and drops by decay_amount every decay_interval seconds down to floor_price
+    DutchAuction {
+        start_price: U128,
+        floor_price: U128,
+        start_time: TimestampSec,
+        decay_interval: u32,
+        decay_amount: U128,
+    },
+    /// edition N (0 based) costs base_price + N * increment
+    LinearCurve { base_price: U128, increment: U128 },
+    /// edition N (0 based) costs base_price * (1 + growth_bps / 10_000)^N
+    ExponentialCurve { base_price: U128, growth_bps: u16 },
+}
+
+impl PriceStrategy {
+    fn assert_valid(&self) {
+        match self {
+            PriceStrategy::DutchAuction {
+                start_price,
+                floor_price,
+                decay_interval,
+                ..
+            } => {
+                assert!(
+                    start_price.0 < MAX_PRICE,
+                    "Nearlend Dao price higher than {}",
+                    MAX_PRICE
+                );
+                assert!(
+                    floor_price.0 <= start_price.0,
+                    "Nearlend Dao floor_price is higher than start_price"
+                );
+                assert!(
+                    *decay_interval > 0,
+                    "Nearlend Dao decay_interval must be positive"
+                );
+            }
+            PriceStrategy::LinearCurve { base_price, .. } => {
+                assert!(
+                    base_price.0 < MAX_PRICE,
+                    "Nearlend Dao price higher than {}",
+                    MAX_PRICE
+                );
+            }
+            PriceStrategy::ExponentialCurve {
+                base_price,
+                growth_bps,
+            } => {
+                assert!(
+                    base_price.0 < MAX_PRICE,
+                    "Nearlend Dao price higher than {}",
+                    MAX_PRICE
+                );
+                assert!(
+                    *growth_bps > 0 && *growth_bps <= 10_000,
+                    "Nearlend Dao growth_bps must be between 1 and 10_000"
+                );
+            }
+        }
+    }
+
+    /// Price of the next token when `minted` tokens of the series already exist
+    fn price_at(&self, now: TimestampSec, minted: u64) -> Balance {
+        match self {
+            PriceStrategy::DutchAuction {
+                start_price,
+                floor_price,
+                start_time,
+                decay_interval,
+                decay_amount,
+            } => {
+                if now <= *start_time {
+                    return start_price.0;
+                }
+                let steps = ((now - start_time) / decay_interval) as u128;
+                start_price
+                    .0
+                    .saturating_sub(decay_amount.0.saturating_mul(steps))
+                    .max(floor_price.0)
+            }
+            PriceStrategy::LinearCurve {
+                base_price,
+                increment,
+            } => base_price
+                .0
+                .saturating_add(increment.0.saturating_mul(minted as u128))
+                .min(MAX_PRICE),
+            PriceStrategy::ExponentialCurve {
+                base_price,
+                growth_bps,
+            } => growth_factor(*growth_bps, minted)
+                .map_or(MAX_PRICE, |growth| apply_growth(base_price.0, growth)),
+        }
+    }
+
+    /// Prices of the next `count` tokens
+    fn quote(&self, now: TimestampSec, minted: u64, count: u64) -> Vec<Balance> {
+        (minted..minted.saturating_add(count))
+            .map(|minted| self.price_at(now, minted))
+            .collect()
+    }
+}
+
+/// `(1 + growth_bps / 10_000)^exponent` by squaring, as a mantissa in [10^18, 10^19) and a
+/// power of ten, rounded down at each step. `None` once it would take any price past MAX_PRICE
+fn growth_factor(growth_bps: u16, exponent: u64) -> Option<(u128, i32)> {
+    const ONE: u128 = 1_000_000_000_000_000_000;
+    let mul = |(a, a_exp): (u128, i32), (b, b_exp): (u128, i32)| {
+        let mantissa = a * b / ONE;
+        if mantissa >= 10 * ONE {
+            (mantissa / 10, a_exp + b_exp + 19)
+        } else {
+            (mantissa, a_exp + b_exp + 18)
+        }
+    };
+
+    let mut growth = (ONE, -18);
+    let mut base = ((10_000 + growth_bps as u128) * ONE / 10_000, -18);
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            growth = mul(growth, base);
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = mul(base, base);
+        }
+        // 10^33 times the smallest price is MAX_PRICE
+        if growth.1 + 18 >= 33 || base.1 + 18 >= 33 {
+            return None;
+        }
+    }
+    Some(growth)
+}
+
+fn apply_growth(price: Balance, (mantissa, exp): (u128, i32)) -> Balance {
+    let mut price = price;
+    let mut exp = exp;
+    while price.checked_mul(mantissa).is_none() {
+        price /= 10;
+        exp += 1;
+    }
+    let price = price * mantissa;
+    if exp >= 0 {
+        10u128
+            .checked_pow(exp as u32)
+            .and_then(|scale| price.checked_mul(scale))
+            .unwrap_or(MAX_PRICE)
+            .min(MAX_PRICE)
+    } else {
+        10u128
+            .checked_pow(-exp as u32)
+            .map_or(0, |scale| price / scale)
+            .min(MAX_PRICE)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -97,6 +435,313 @@ pub struct TokenSeriesJson {
     creator_id: AccountId,
     royalty: HashMap<AccountId, u32>,
     transaction_fee: Option<U128>,
+    ft_token_id: Option<AccountId>,
+    public_sale_start: Option<TimestampSec>,
+    sale_start: Option<TimestampSec>,
+    sale_end: Option<TimestampSec>,
+    is_sale_open: bool,
+    max_per_account: Option<u32>,
+    price_strategy: Option<PriceStrategy>,
+    reveal_commitment: Option<Base58CryptoHash>,
+    is_revealed: bool,
+    is_random_editions: bool,
+    is_metadata_frozen: bool,
+}
+
+/// `msg` expected by `ft_on_transfer` when buying a series priced in a fungible token
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtBuyArgs {
+    pub token_series_id: TokenSeriesId,
+    pub receiver_id: Option<ValidAccountId>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PresaleAllowance {
+    quota: u32,
+    minted: u32,
+    price: Option<Balance>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PresaleAllowlistEntry {
+    pub account_id: ValidAccountId,
+    pub quota: u32,
+    /// overrides the series price during presale
+    pub price: Option<U128>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PresaleAllowanceJson {
+    account_id: AccountId,
+    quota: u32,
+    minted: u32,
+    price: Option<U128>,
+}
+
+/// Trait of a single edition, listed under `attributes` in the token `extra`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Attribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+/// Metadata of a single edition, the fields left out fall back to the series metadata
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EditionMetadata {
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+    pub attributes: Option<Vec<Attribute>>,
+}
+
+impl EditionMetadata {
+    fn assert_valid(&self) {
+        assert_valid_hash(&self.media_hash);
+        assert_valid_hash(&self.reference_hash);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EditionMetadataEntry {
+    pub edition: u64,
+    pub metadata: EditionMetadata,
+}
+
+/// Optional sale settings of `nft_create_series`, the ones left out are unset
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleConfig {
+    pub ft_token_id: Option<ValidAccountId>,
+    pub sale_start: Option<TimestampSec>,
+    pub sale_end: Option<TimestampSec>,
+    pub max_per_account: Option<u32>,
+}
+
+/// Fields of the contract metadata to update, the ones left out are kept
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractMetadataUpdate {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub icon: Option<String>,
+    pub base_uri: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+/// Fields of the series metadata to update, the ones left out are kept
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SeriesMetadataUpdate {
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+    pub extra: Option<String>,
+}
+
+/// Receiver of `nft_mint_batch`, `count` defaults to 1
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchMintReceiver {
+    pub receiver_id: ValidAccountId,
+    pub count: Option<u32>,
+}
+
+/// Result of `nft_mint_batch`, receivers from index `processed` on were not fully minted to,
+/// `partial_count` is the number of tokens already minted to the receiver at `processed`
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchMintJson {
+    pub token_ids: Vec<TokenId>,
+    pub processed: u32,
+    pub partial_count: u32,
+    pub completed: bool,
+}
+
+/// English auction for a single edition series, bids are in NEAR
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Auction {
+    reserve_price: Balance,
+    min_bid_increment: Balance,
+    end_time: TimestampSec,
+    /// a bid placed less than this many seconds before end_time pushes end_time back to now + extension
+    extension: u32,
+    bidder_id: Option<AccountId>,
+    bid: Balance,
+}
+
+impl Auction {
+    fn to_json(&self, token_series_id: TokenSeriesId) -> AuctionJson {
+        AuctionJson {
+            token_series_id,
+            reserve_price: self.reserve_price.into(),
+            min_bid_increment: self.min_bid_increment.into(),
+            end_time: self.end_time,
+            extension: self.extension,
+            bidder_id: self.bidder_id.clone(),
+            bid: self.bid.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionJson {
+    token_series_id: TokenSeriesId,
+    reserve_price: U128,
+    min_bid_increment: U128,
+    end_time: TimestampSec,
+    extension: u32,
+    bidder_id: Option<AccountId>,
+    bid: U128,
+}
+
+/// Roles for admin methods, the contract owner passes every role check
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    FeeManager,
+    TreasuryManager,
+    Pauser,
+    SeriesCurator,
+    Upgrader,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Admin,
+        Role::FeeManager,
+        Role::TreasuryManager,
+        Role::Pauser,
+        Role::SeriesCurator,
+        Role::Upgrader,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::FeeManager => "fee_manager",
+            Role::TreasuryManager => "treasury_manager",
+            Role::Pauser => "pauser",
+            Role::SeriesCurator => "series_curator",
+            Role::Upgrader => "upgrader",
+        }
+    }
+}
+
+/// Code waiting in `staged_code`, can be deployed to this account from `activation_time`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StagedCode {
+    pub code_hash: Base58CryptoHash,
+    pub staged_at: TimestampSec,
+    pub activation_time: TimestampSec,
+}
+
+/// Proposed owner, takes over once it calls `accept_ownership` before `expires`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingOwner {
+    pub owner_id: AccountId,
+    pub expires: Option<TimestampSec>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnerJson {
+    pub owner_id: AccountId,
+    pub pending_owner: Option<PendingOwner>,
+}
+
+/// Operations that can be paused independently
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PauseOperation {
+    Mint,
+    Transfer,
+    Burn,
+    CreateSeries,
+}
+
+impl PauseOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PauseOperation::Mint => "mint",
+            PauseOperation::Transfer => "transfer",
+            PauseOperation::Burn => "burn",
+            PauseOperation::CreateSeries => "create_series",
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    pub mint: bool,
+    pub transfer: bool,
+    pub burn: bool,
+    pub create_series: bool,
+}
+
+impl PauseState {
+    pub fn is_paused(&self, operation: PauseOperation) -> bool {
+        match operation {
+            PauseOperation::Mint => self.mint,
+            PauseOperation::Transfer => self.transfer,
+            PauseOperation::Burn => self.burn,
+            PauseOperation::CreateSeries => self.create_series,
+        }
+    }
+
+    fn set(&mut self, operation: PauseOperation, paused: bool) {
+        match operation {
+            PauseOperation::Mint => self.mint = paused,
+            PauseOperation::Transfer => self.transfer = paused,
+            PauseOperation::Burn => self.burn = paused,
+            PauseOperation::CreateSeries => self.create_series = paused,
+        }
+    }
+}
+
+/// Collateral lock, while it exists only the locker can move or burn the token
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenLock {
+    pub locker_id: AccountId,
+    pub reason: Option<String>,
+    pub locked_at: TimestampSec,
+}
+
+/// Rental user of a token, holds the utility of the token until `expires` without owning it
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenUser {
+    pub user_id: AccountId,
+    pub expires: TimestampSec,
+}
+
+/// Token returned by the view methods, NEP-171 token with the current rental user
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenJson {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub metadata: Option<TokenMetadata>,
+    pub approved_account_ids: Option<HashMap<AccountId, u64>>,
+    pub user_id: Option<AccountId>,
+    pub user_expires: Option<TimestampSec>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
@@ -114,16 +759,6 @@ pub struct MarketDataTransactionFee {
 
 near_sdk::setup_alloc!();
 
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct ContractV1 {
-    tokens: NonFungibleToken,
-    metadata: LazyOption<NFTContractMetadata>,
-    // CUSTOM
-    token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeries>,
-    treasury_id: AccountId,
-    transaction_fee: TransactionFee,
-}
-
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
@@ -134,6 +769,25 @@ pub struct Contract {
     treasury_id: AccountId,
     transaction_fee: TransactionFee,
     market_data_transaction_fee: MarketDataTransactionFee,
+    presale_allowlist: LookupMap<TokenSeriesAndAccountId, PresaleAllowance>,
+    minted_per_account: LookupMap<TokenSeriesAndAccountId, u32>,
+    auctions: UnorderedMap<TokenSeriesId, Auction>,
+    token_locks: LookupMap<TokenId, TokenLock>,
+    token_users: LookupMap<TokenId, TokenUser>,
+    roles: LookupMap<Role, UnorderedSet<AccountId>>,
+    paused: PauseState,
+    pending_owner: Option<PendingOwner>,
+    staged_code: LazyOption<Vec<u8>>,
+    staged_code_info: Option<StagedCode>,
+    upgrade_timelock: TimestampSec,
+    /// NEP-145 prepaid storage, including the registration minimum
+    storage_deposits: LookupMap<AccountId, Balance>,
+    /// metadata set by the creator for editions that are not minted yet
+    edition_metadata: LookupMap<TokenId, EditionMetadata>,
+    /// fungible token payouts that failed, e.g. to accounts not registered with the token
+    ft_payouts: LookupMap<FtTokenAndAccountId, Balance>,
+    /// account that paid the storage of each token, refunded when the token is burned
+    storage_payers: LookupMap<TokenId, AccountId>,
 }
 
 const DATA_IMAGE_SVG_NEARLEND_ICON: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAEQAAAA+CAYAAACSqr0VAAAACXBIWXMAAAsTAAALEwEAmpwYAAAAAXNSR0IArs4c6QAAAARnQU1BAACxjwv8YQUAABePSURBVHgBzVt7rGVXWf++tR/nPmbu3EfnUca+qIIobShTAkXFEkMJJNapPEuIEuEP/wBpUAj+R8SIQSgaxEfVpFJEcExnoAFUAkaCEI1VCA2POuXR13Tmdua+z9x7zt5r+ft9a+19zr1zZ3rudErckz1rn/066/ut3/f7vvWtc1V+DFsQUZmc3Ce7du0V1b0SwhROT2Ifw+7iLc3NoSfOreO+Fen1FnB8BmdP6Pz8qvwYNpVLvAUauH//i3B4E4x7LtoXwLiDOB4YrhoGt/P/sPnzua3H/gT2H+DZB3H/f0hdf1tPn35MLvF2yQAJ+/c/H83t2F+NfU6GjYr2huHb5fzGn9sOA+iBjXN8Z43P9+P4czj+gp48uSaXYHtagJgrXHbZDejUHfj4C7ITI8/HlguzZvtzIaxKlv2DrKwc0bW1k/I0tosGJMzOTklRvBcjdhuMyprTm1oa630AYCLnMmVUgze/jxvf2ZwfBpT6I/Ix6M2n5SK3iwIkTE9PS6fzYRy+VLZ3jdEZMswIAjdsrPVQLwY4utAfyEVsFwfIvn0fRPMquZSj+9TtKC41/H2f11OnPiQ73HYMCMC4Cc3HZGvnLtXobjb4wuA+FYDef0CffPLfZAfbjgCxkLpv3z3o7E89w6O7cwC3Y1wIy1KWb9VHHz0rI2657GQ7cOAQUL8aX9y/YMfO1ZLBdXZadetzoxkdDR6cYwgevnauaI8hubsB7ddkxG1ngITwUuyVnNvheNyM0KiUHj639ZnttGYzs7YDdrv3vVieMUC8fw6+aCsgz8zobr3/3HcNPm+nXQ14IVwVDh0q9P77+zLCNjIgeDtzjVns/RE7uPnaBUa3hiBXHfhhJ9N+gbZU53OnPlOhaa6qQ2fD1501X493q3ob8AbvHe4HW+cm5fjxXThekBG20Rmyf38zEeu3I7LZ2E0drLFXbrfrZZNZ7UrxWmitueI0rjMqVaEqaoBQiR+HjRk+l7X63IsvRarCARjnajxSA6SqYJvhoA7j3brafabqzT2xsT5zYmMjq/32IZwsZNfyfOLSA1LXDmjXBsh2UQDnAsxdd9PZ8thPdLr5TBHwelgQXKh9VlceEAWB4XXppS4zDZkPuCB1AUDyWnwBy/JK6ixInXutcgkegFS2Z1rngHGs0NWZTvHEVVp+p9Cpou/rAz/srl77jZWl6fmNnmzHnLLMRjVzdEAK9LquN1KavkkDalfocnlFZ2Hi2omNbCpTB5rXfQDR9xqcJyh1pt5jUhbyDIb7ELLMWFEXuJk7PtcZwXAEBue8rwpPt3EREAEgGqrcOYJkzMmdbIxn7sFD03u++6KZ6ZkT693rvrYwf8WDa6ubdKWqeqOaOTogJ070ZO9ezigL+5wYsdC5duzU1PVT624K3auDgg0uVF4gAYH+H9iqlwxgZMaEEIo6UB9okOC45nljCsHq43xujKlzuxb6OTSldFpnQLUEzwygQFCE5/sO1wuVE8+enHjsOZPXzJ7srb3wX+Yfv+bbK8scL4Te9VHNHBkQkK8CAKdxSIEKa+X+8pE9Pz+7Uh4YIzaurqAklYdfedzH4fG5lL6mYGBQJYcfJEDIBE8m5GQKJAkA1Hk/ug2ACiRjDkfLnII9ABCuBaPhMmSKkC14HrrCd+DLOBSUl0xcAItOXD0xed9vXv3c/Q93F2/+1OPf2r9+WVcWF0e1c/QNNY+3gYov/t+5V+87Pfm8qaDoWagDGaGhn9gBz5C+N93AjvHEiSqyg64Cw71jW0c2AKdQRlEFIwwMXxIQeGWnr3X6TPfpw11CFFhHAMAQV1F40zEApAiTLWATRgaM6o3r6ftv2fvXfVn61/v0vu5T2bijPGSpPPDw8blbX7tWPmtcAsWSskDrLDj6zMN4ARAYQBAaXgzXIX3ADgJgggpmmNtAPD3O13mldCcYoIgGgQCYC5XQE4w22ERXASuirtBYuAzdBoY7T7czMHAv2OLx5WSLi8+qntk3VgWvv5TL9AsOr7/hU8fGPv3QhWwcmSEf+dVw+WT3obc+e/mrt6inWNYREDM66kaG/gqZAVAU4DiHEMDAlDOaIJ/L69hCF3xBgwlSCOYiBQGqorsgHENYLSw3DPE8RnQKZEphBqedDBG0mbPwjEgEF3URFHEPP3/PQ/MHOisYMww+sPXyuevya7/4Pn2fl4tlyJ23h5+FTh8+W16e9XRivZAuJAwx0/VhjaPLBNMKEoOMwLeTORX0gMZH3XCiAIZJGIzC+cxCbgTAmaZQZ8AEaEIVhRZtnzoChni8o8owDgQ1r31dghlOhdc99SYPZAg1KBg78DV4Z1icybvoE/Mn7FqGPLzxG/VDl78qvOrvv6Bf2NgxIHfeFn4xrMsrwKWqyifqs8X0EsLpHExgOhYy6ifcJe7eEyi4E8ACCORfHg0nO4RsYYcNiBh16jwKbMhcDLmO7pNJzEdobB21JTcRTuey0KcuAYiAnK+mm/A6hBfCyk4RnNAbzzZ6Y5l30U6mCyAxeuD0lo6f23NTeN1dX9cjZ0cC5H03h3zPjNyKt93ECEOIGTqWx645OdZfnIPkBUa7mgEfEUYduY8Wg8hRwkWGZhgLNuQUVSYnLrZkDXMOixLORp+ffZFZpOlnNXUFRhLMnmmId3AZAphlKSQD2DJQJwJCcWQEznGEqgydwfOLe4tFjdm1S4A49NnZOKq+bL8fn/nJ8I4PHNePtkxx24KBru+alcPo1s1GepjEFm/Kl8vLl72D+CHC1FqAJAU6UwQAD7dBm5V2rcpK6RcduEBhxz4vA9uqKNAWMBrHWQfPYtf4uY+cr4/7fc57+A7seEeN+ysKbhav1Q7fjeM6w7lSmKzF3ZEp0W0q3Lqwd4x5SMZ5WAIGxz4ekykqN14ni7cN274tILteI7eieUUwOYxgGCgOcaKY9Ov59KoHCDSkhhFsPQyzcw6G5zAiL8EAnKfRMLKnDRgwnNeS0TDKwKi5F7iWgDCACEQCr8469rnvMAFMnz2BIVAs1pFpZZ7AETk76dbXdxX9xlWsuKXxmDtsicwJ8htvDG+8+ryA3PnacAvQO6wR1fiCBIpPaK92Ds5jsmYAEAjJSuKG4zKYQXljTMmRjQYVNBQTPAOjjAxx8b7ajMb9rmOG1saeIoLkEmjasKK0z7Wl+mVsmdKjl9CSEBM+iOnesSWEOte6jEaWcA/RkWGbN/s2fH7HtoDc+bowC1G4lUxoqEYFCAkUSeK0OPbs0+Yu6GR0FRhmxmEClka9zuJox05Hg6NrFC0IZrSLjLBnAF6lZeMOtgM05XOB7oJ7MRBSYXxqMs7lKM50LMogJ7FIxgkhygf18mzRbQc0GT7YXQNUZAtc53B4ywtEtogqLHsHLu6DMHD2jvTK5ovKbCU0k30cQyOq1bGD85PrJ+acQ0aFIlpAZylk0Fl0vG9RmZ8riCEENlAkGW04NowuHtRnomZzmjSXYRQy4cRrQnt/OocEDaJsz8doxIkfhBcQ+rLno9tAYNEu7wM7ABA6a9+GqGuxZwCIxyBrPgDMcer5Jhx/owXkjw6Hm/CGQ4wojJYsIzB6Qy0THhEziefDQufKM+O9+TmkXEAKUzjH8ElP9REAJlRqROVchqk58wkc90nkwDHDaq8ydwgREJvjBOYdLs18CaYSjF4EyCFrtX94Dt+DPERjZor76Qiwhu9emCtXo04EaoQb0pFWYOMe85OkLy+9OdycJ0CYT8mvpQfa5XgCoc0NsqmACiGbrLrF7MpYtTgBwsJ4dFgrYKPWscDvMjBslClnIbYcRYyqhWDMa1gxQhdhCKvPzEOS4WYcY4IZLQTCkU0aJ4ZkD4YCVMa74EJlbcvpKzP5KjJW9Bdq70PWaAfDIGc3ZE0CJbPwK5rACmNzcvU1BsiHb5PrcdNPweI60YFpRGDeEZdbIqQEpY6Amb2rnStPd6rVCXYNI2wSpkzL4aLWEmZnM1HzOAxIsFFnKYBMMe/OYk4Cww0YAuSS0cYAgiRkHUuNaufwjgrI41sjS6hl5lYuLF02tpLYgCtqrmKhFglIGGKGsSKumhuD1By2OmiAYDRe6YhkzO7biqVLtHCudRhJ7sTBlI1iqr9RzHTzemVMNM5HzH+cZz1EYyIGYxIL6Do4z0zVwAhqYKiBoAaOJPYY072NPnSIPXGYGVM3yBXrGV2HhRaLJVYbWZvO18AOMb1Q6qNGdqQIqUOuo6kdFls8NGGA4OKNFkUsHbWxlLaMS2akVtLbfUKN+Cx2rjgz1z3+LK89iYygTkS20F3EqO8trfMZxJajL5EFMeNPbkLApDY3oWhS+ytnuh4ZklyyTgyhPlEmYwaLhK5QvzTb6TJ9xkPoptroaxtVQmQGe2C6om4ARhRccKKbf+RwmMYs4yp8by0DAWm1w/wnxqimktxe42fMb6r1YmYt1zO7vEWU2m72vMk6j/uowkmQKJIGDta6zM3SeR/HlcbHSCbBXMIAtOK7lzpGvAgGdxfnBywnrs0UXdOapBubw2zUDonpA5gRXSm5zhBjNs7k8KBZjbmGJraERkEbESVDfFMoGFLXJhqtdA4s7emtTELpM0x9+bwnWSmMhr0xgcfRhUwXTChdjO+mD1aON7ZYZm2RhPZWBoyxKPE0sD6p0CAoEoW1P6ZVd6rciARumBFSVLFY2YDT6Eec6LURx+7p9WT3cXMZJl+uGfWQXKZxk2R/w5AWtCF8kLFWG+6yZXXzcxQcLKQ49tm7KDZDrMGIc95Ta1yLiN0xsE1nXJwQJlb4CJKaK1I94DrMUUxbJL6A963OZms+KtGAHRZVJImqukZLUgbeHjcug55+8z69q5uj5rK07mK41SEMNOmJDPtHk6SFIZ1JbtUt9612ZGkaJd/MBFRt9KyOqsYE0wE82qfIRjfKjAnGHs+kzFGUCwunwcWUx1sJn603pgR7qQFKd9TeWNE/u9tXNmaqrZuwTBOP1RQppOOQmKND2iEx3PwzjXFvP6an8fa1pL65DNL0+BCPh9J2m9co61zSZHrt+V6+f7F2VlG23EAypI5ICCtWsLBUgfDINBxtFqtattCTOzDMUtnAY0zP6W/Ihu2ZmvdKYfN6tnyv+SMTGqTv61OyrmpOniURTUC45C6SbWaDDPQk5SRoUQTO/1GkSd2dfBPIvyyNemj8hK8jN8PAcxo/aUNzXAuKn3092e+NT/by0C2j9ylzkWD5otYWLdjPRkhNpF1ayG0+a6RnSNIqSVw5jxGW46wkx3wYcS3XXjW+FMy4c4Q0jb5KylY11kEMnKhs0gAY5DPH3N2LLSCIMl/C1ZdvMlpSRHGmYrQuDzp0aRBu2iwW6wB13+1aR62qjPEg2FcHe4Wt44SY/loIslDrbbbUZ04SQzTXMLiezkTT7g2W1aFAaJph9UkyDYHKikS7u0wm21ltrHeoi7phQ9G6isjmND6xJmi/+vPGMNPSJyv5PG5Yb1wlAWWuksIS3cS1iU2ItZGU3Ng9rmn74xXWc3tcZfaWHYLyoDjmJzAkgzsVdo7u4um6ai6W3IfuQk8somuxRmjP0/Usy1arVXHGm4/VTMd7kyshDIzM6Srt1N76HNzmiV1kSxJW5iRfunfsk9/fBMgHP6srePhvgg5AaIGJWpEz81Mr7CUgQjxn55OOEBR3FpMKVM6qYryKxiIlNg3Joja4qBHe8t+oMYF6YHtuRhMYzgiDtaYfVmxFimHPotTgiVC1a82zCr+tq5BiIQKUJnHtvGWQiMmy72W/P+wVbT3kqkw+CoMfT5OhgVhKy5L2WFJJsRFWdCn3DShUy954v9KO5x4w0sQsLt8510Y+LktKbkbHNrKBa54EhedRYgdTXLonnmNhyqbEqB/05xZ7AxdIpcGBsYkFwUoAae5uOYcmlgCwvzg2fvcPtwXk9UeUi2W/qzoAo3EHE6zEHNUBUMOgDM8Nso2Ole5QHPI28TLqsq5LBtCVmPZkkQFwDwOgcQlLRjg7zBMoWYpAGaORfR1vZg7bm1msh6OKb5OvCERiipOUmTZakhKy/8nd+l/Jlm1TxeyOe/Ur8Mujqm0C02pGw5zG6KYjbTiLQEXQsCCTnR3r0cLKjChYHjChs1Vva51pC433cdZnABk4triRDc4Z21lTKKjCFnc3Zhb69fh6CG01zKfqV0p17dmmj6HJRjOxAoWeQX/eeUSP9C4ICDfUMv4QL3pUBqDkDSheNrFhUHFqxFgH+UrWndhIs27HQnQqN7q4PmGrVdaGBEZcTAE4LJRo7qKLJcaEPJbcojrzWnb2qse6KXxmyfDIjBC25ByboopFHQz6h4/pZlc5LyDvOaJP4KE3YX8kGdeIZhRcDHfSizzIUMLmNyV1yLAyLZd3ryJacmGX+aoJolHfxFEHTOEkR0l5uJKzaTFdRCVFGXMhaIawlAZ/25g73evDXbYaPRR+t4hsUxcBYBr+9Fj+8U/IebZtlyF+5179EWo2b4Px8zqkF0NIb8r+tNEZbyWE9pxb71T52fH1yCoalCUXsAmPBSUGLtVYLCHdOYvTqB2sI/A5AMaw5OIOhFae9+DKoHIeC8bmKmkmGxITpHXxJknTLx/Tez4oF9jc+S7c8Vn9HpYJ34IvPTWkF61rWHTZRnz50xiG78al8qXda66Xx6pySMAQEOdaxbciqhVTNRrPhIUxmyDVfI7LdQRFs/XLT3SrPSt17A91IWlICE43syVr7kkR5/t7Zezt8hSbu9DFd39aH0AR7M0w7vEhNpgYNKCEQQ4Sly2U7jLQHBpWLs4sspTedpDi4C0VhT9bLm8gGBhkhNcWAGttNYxzE6+L139nSWRrziFpSq/DLpPE1Vxloa/6lrswm5WnAwg3MgWZ0htg4Ld04B5YlWqTsoYhpjUGjBuMFEHJ+nkol/YsKourRmsXtca7FG8j1VPGFs8NgWKzQQC19PzvLVaT3TTLCi0DRIZnsj4fBgs3LyHc/fp9+vGHZYTtKQHh9tuf0UfGc7kdL7+nTcx0kK3a8ZDOaErtm+hDUEpEnWJheoGjrTZxcXEmSoYkN4miGafCdtzeBzm6bH5j5We+2xSQWyC2zmQN7AQQWtRJwus/p/c8ICNuKjvYOLG68zXyTnSCS3+sQbDsiBpwaptzbDUesxrEY43rPfXG9OIUEqo5+/EMK2m2FhPiL4xsbcfz55po+csi/lCmUrCieuyXP/9oPb3C317wR38tIyVObvLmfNoZ01cRUd57VD/xRdnBtqOfVGlcu/vjO38lnMJU/vdMuKxkJrL1byvbAnWq0TaXOkvTS6xb9OcW5mzSa8WqysppNrXmdJdxhuVEKzQgi7rx/ieq6WWWVHNNZUKVsMlVxIqT2ujcAm55z1H926/IDreRXGbr9q7P6Cdh4GEcnpAmIfNDIViGCkwpN2m1BsedpZmF4szMaQuxFNMksLESS3dhid2ZmC5d/50nV593fKV5vx9yDx3SkqCNloSHEeXedu9FgHHRgHB791F9YPyM/VUV/77Nh5TNDmmJGZ+WNzaBwuPO4sxCeWrv46g4ovQaQ67lIj6Gbs5uz9zwrVNP/tzX5814N8hGZai2MZQScCp/X75WvPnoDjRj67YjDTnf9qHXhJ+Gke/Hfp3IQDOEv22V+LN3tlxJCMPXqTOognQPPnGwv3tl2ualrrLp15Mv+c/H51/+7wDDfizHH2lSF5pF6kYrAC60Q90pvOv9R/XjX5WnuV0SQLhxKedDr5VbMbq/BWYcoLFuKyi2tBIB4Tn+Zi4BVfX3LO86e+DUwf7USufkK7/8g4UbvrnESlHMc0w08zTD4/J4OodasOrf7RH3ybv17pF/rXyh7ZIB0mx/eSgUa1fJ6+BDt8OXrxRJwAxHHbZN5NEEmpXj5asPvOdP/mvpJf99qPb+RvjJdIweYZgZTMu/jar6V6YlP3qpgGi2Sw5Is/FHe1PT8sI6l1eCAdcBlCsscCS3CdqGaf65+teRnP/Tu45o+6fr/GnCrDzrSt8vZvJCJ7z0sdagJx+TiR/dr3eN9MdAF7M9Y4Bs3f7sTWFm7awcxPhOUmyhrqtwgEcAwhn5f7T9H0q0uiq7AUuJAAAAAElFTkSuQmCC";
@@ -148,8 +802,21 @@ enum StorageKey {
     // CUSTOM
     TokenSeriesById,
     TokensBySeriesInner { token_series: String },
+    UnmintedEditionsInner { token_series: String },
     TokensPerOwner { account_hash: Vec<u8> },
     MarketDataTransactionFee,
+    PresaleAllowlist,
+    MintedPerAccount,
+    Auctions,
+    TokenLocks,
+    TokenUsers,
+    Roles,
+    RoleMembers { role: Role },
+    StagedCode,
+    StorageDeposits,
+    EditionMetadata,
+    FtPayouts,
+    StoragePayers,
 }
 
 #[near_bindgen]
@@ -181,7 +848,7 @@ impl Contract {
     ) -> Self {
         assert!(!env::state_exists(), "Already initialized");
         metadata.assert_valid();
-        Self {
+        let this = Self {
             tokens: NonFungibleToken::new(
                 StorageKey::NonFungibleToken,
                 owner_id,
@@ -200,28 +867,41 @@ impl Contract {
             market_data_transaction_fee: MarketDataTransactionFee {
                 transaction_fee: UnorderedMap::new(StorageKey::MarketDataTransactionFee),
             },
-        }
+            presale_allowlist: LookupMap::new(StorageKey::PresaleAllowlist),
+            minted_per_account: LookupMap::new(StorageKey::MintedPerAccount),
+            auctions: UnorderedMap::new(StorageKey::Auctions),
+            token_locks: LookupMap::new(StorageKey::TokenLocks),
+            token_users: LookupMap::new(StorageKey::TokenUsers),
+            roles: LookupMap::new(StorageKey::Roles),
+            paused: PauseState::default(),
+            pending_owner: None,
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            staged_code_info: None,
+            upgrade_timelock: DEFAULT_UPGRADE_TIMELOCK,
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            edition_metadata: LookupMap::new(StorageKey::EditionMetadata),
+            ft_payouts: LookupMap::new(StorageKey::FtPayouts),
+            storage_payers: LookupMap::new(StorageKey::StoragePayers),
+        };
+        migration::write_state_version();
+
+        this
     }
 
+    /// Migrates the state from any previous version, see `migration`
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let prev: ContractV1 = env::state_read().expect("ERR_NOT_INITIALIZED");
-        assert_eq!(
-            env::predecessor_account_id(),
-            prev.tokens.owner_id,
-            "Nearlend Dao Only owner"
-        );
+        let versioned = migration::VersionedContract::read();
+        let from_version = versioned.version();
+        let this = versioned.into_current();
+        // called by an upgrader after `near deploy` or by the contract itself from `deploy_staged_code`
+        let caller_id = env::predecessor_account_id();
+        if caller_id != env::current_account_id() {
+            this.assert_role(Role::Upgrader);
+        }
+        migration::write_state_version();
 
-        let this = Contract {
-            tokens: prev.tokens,
-            metadata: prev.metadata,
-            token_series_by_id: prev.token_series_by_id,
-            treasury_id: prev.treasury_id,
-            transaction_fee: prev.transaction_fee,
-            market_data_transaction_fee: MarketDataTransactionFee {
-                transaction_fee: UnorderedMap::new(StorageKey::MarketDataTransactionFee),
-            },
-        };
+        NearEvent::log_state_migrate(caller_id, from_version, migration::STATE_VERSION);
 
         this
     }
@@ -229,21 +909,32 @@ impl Contract {
     #[payable]
     pub fn set_transaction_fee(&mut self, next_fee: u16, start_time: Option<TimestampSec>) {
         assert_one_yocto();
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.tokens.owner_id,
-            "Nearlend Dao Owner only"
-        );
+        self.assert_role(Role::FeeManager);
 
         assert!(
             next_fee < 10_000,
             "Nearlend Dao transaction fee is more than 10_000"
         );
 
+        // applies a scheduled fee that is due, one still pending is replaced by this change
+        self.calculate_current_transaction_fee();
+        if let (Some(scheduled_fee), Some(scheduled_start_time)) = (
+            self.transaction_fee.next_fee,
+            self.transaction_fee.start_time,
+        ) {
+            NearEvent::log_fee_schedule_cancel(
+                env::predecessor_account_id(),
+                scheduled_fee,
+                scheduled_start_time,
+            );
+        }
+
         if start_time.is_none() {
+            let old_fee = self.transaction_fee.current_fee;
             self.transaction_fee.current_fee = next_fee;
             self.transaction_fee.next_fee = None;
             self.transaction_fee.start_time = None;
+            NearEvent::log_fee_update(Some(env::predecessor_account_id()), old_fee, next_fee);
             return;
         } else {
             let start_time: TimestampSec = start_time.unwrap();
@@ -253,6 +944,12 @@ impl Contract {
             );
             self.transaction_fee.next_fee = Some(next_fee);
             self.transaction_fee.start_time = Some(start_time);
+            NearEvent::log_fee_schedule(
+                env::predecessor_account_id(),
+                self.transaction_fee.current_fee,
+                next_fee,
+                start_time,
+            );
         }
     }
 
@@ -276,9 +973,12 @@ impl Contract {
         let transaction_fee: &TransactionFee = &self.transaction_fee;
         if transaction_fee.next_fee.is_some() {
             if to_sec(env::block_timestamp()) >= transaction_fee.start_time.unwrap() {
-                self.transaction_fee.current_fee = transaction_fee.next_fee.unwrap();
+                let old_fee = transaction_fee.current_fee;
+                let new_fee = transaction_fee.next_fee.unwrap();
+                self.transaction_fee.current_fee = new_fee;
                 self.transaction_fee.next_fee = None;
                 self.transaction_fee.start_time = None;
+                NearEvent::log_fee_update(None, old_fee, new_fee);
             }
         }
         self.transaction_fee.current_fee as u128
@@ -304,46 +1004,438 @@ impl Contract {
     #[payable]
     pub fn set_treasury(&mut self, treasury_id: ValidAccountId) {
         assert_one_yocto();
-        assert_eq!(
+        self.assert_role(Role::TreasuryManager);
+        let old_treasury_id = std::mem::replace(&mut self.treasury_id, treasury_id.to_string());
+        NearEvent::log_treasury_update(
             env::predecessor_account_id(),
-            self.tokens.owner_id,
-            "Nearlend Dao Owner only"
+            old_treasury_id,
+            treasury_id.to_string(),
         );
-        self.treasury_id = treasury_id.to_string();
     }
 
-    // CUSTOM
-
+    /// Updates `nft_metadata`, storage growth is paid from the caller's storage balance
     #[payable]
-    pub fn nft_create_series(
-        &mut self,
-        creator_id: Option<ValidAccountId>,
-        token_metadata: TokenMetadata,
-        price: Option<U128>,
-        royalty: Option<HashMap<AccountId, u32>>,
-    ) -> TokenSeriesJson {
+    pub fn update_contract_metadata(&mut self, metadata: ContractMetadataUpdate) {
+        assert_one_yocto();
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.tokens.owner_id,
+            "Nearlend Dao Owner only"
+        );
         let initial_storage_usage = env::storage_usage();
-        let caller_id = env::predecessor_account_id();
 
-        if creator_id.is_some() {
-            assert_eq!(
-                creator_id.unwrap().to_string(),
-                caller_id,
-                "Nearlend Dao Caller is not creator_id"
+        let mut contract_metadata = self.metadata.get().unwrap();
+        if let Some(name) = metadata.name {
+            contract_metadata.name = name;
+        }
+        if let Some(symbol) = metadata.symbol {
+            contract_metadata.symbol = symbol;
+        }
+        if metadata.icon.is_some() {
+            contract_metadata.icon = metadata.icon;
+        }
+        if metadata.base_uri.is_some() {
+            contract_metadata.base_uri = metadata.base_uri;
+        }
+        // a new reference without its hash drops the previous hash
+        if metadata.reference.is_some() {
+            contract_metadata.reference = metadata.reference;
+            contract_metadata.reference_hash = None;
+        }
+        if metadata.reference_hash.is_some() {
+            contract_metadata.reference_hash = metadata.reference_hash;
+        }
+        contract_metadata.assert_valid();
+        self.metadata.set(&contract_metadata);
+
+        let final_storage_usage = env::storage_usage();
+        self.internal_charge_storage(final_storage_usage.saturating_sub(initial_storage_usage), 0);
+        if final_storage_usage < initial_storage_usage {
+            self.internal_refund_storage(
+                &env::predecessor_account_id(),
+                initial_storage_usage - final_storage_usage,
             );
         }
 
-        let token_series_id = format!("{}", (self.token_series_by_id.len() + 1));
+        NearEvent::log_contract_metadata_update(env::predecessor_account_id());
+    }
 
-        assert!(
-            self.token_series_by_id.get(&token_series_id).is_none(),
-            "Nearlend Dao duplicate token_series_id"
+    // Ownership
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner_id: ValidAccountId, expires: Option<TimestampSec>) {
+        assert_one_yocto();
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.tokens.owner_id,
+            "Nearlend Dao Owner only"
         );
-
-        let title = token_metadata.title.clone();
-        assert!(
-            title.is_some(),
-            "Nearlend Dao token_metadata.title is required"
+        assert_ne!(
+            new_owner_id.to_string(),
+            self.tokens.owner_id,
+            "Nearlend Dao new owner is the current owner"
+        );
+        if let Some(expires) = expires {
+            assert!(
+                expires > to_sec(env::block_timestamp()),
+                "Nearlend Dao expires must be in the future"
+            );
+        }
+
+        self.pending_owner = Some(PendingOwner {
+            owner_id: new_owner_id.to_string(),
+            expires,
+        });
+
+        NearEvent::log_ownership_propose(
+            self.tokens.owner_id.clone(),
+            new_owner_id.to_string(),
+            expires,
+        );
+    }
+
+    #[payable]
+    pub fn cancel_owner_proposal(&mut self) {
+        assert_one_yocto();
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.tokens.owner_id,
+            "Nearlend Dao Owner only"
+        );
+
+        let pending_owner = self
+            .pending_owner
+            .take()
+            .expect("Nearlend Dao no pending owner");
+
+        NearEvent::log_ownership_cancel(self.tokens.owner_id.clone(), pending_owner.owner_id);
+    }
+
+    #[payable]
+    pub fn accept_ownership(&mut self) {
+        assert_one_yocto();
+
+        let pending_owner = self
+            .pending_owner
+            .take()
+            .expect("Nearlend Dao no pending owner");
+        assert_eq!(
+            env::predecessor_account_id(),
+            pending_owner.owner_id,
+            "Nearlend Dao pending owner only"
+        );
+        if let Some(expires) = pending_owner.expires {
+            assert!(
+                to_sec(env::block_timestamp()) < expires,
+                "Nearlend Dao ownership proposal expired"
+            );
+        }
+
+        let old_owner_id =
+            std::mem::replace(&mut self.tokens.owner_id, pending_owner.owner_id.clone());
+
+        NearEvent::log_ownership_accept(old_owner_id, pending_owner.owner_id);
+    }
+
+    // Upgrade
+
+    /// Stages new contract code passed as the raw call input, replacing any staged code.
+    /// The code storage is paid like other storage, it stays with the contract to hold the
+    /// deployed code.
+    #[payable]
+    pub fn stage_code(&mut self) -> StagedCode {
+        assert!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        self.assert_role(Role::Upgrader);
+        let code = env::input().expect("Nearlend Dao code is required");
+        self.internal_stage_code(code)
+    }
+
+    fn internal_stage_code(&mut self, code: Vec<u8>) -> StagedCode {
+        let initial_storage_usage = env::storage_usage();
+        let code_hash: [u8; 32] = env::sha256(&code).try_into().unwrap();
+
+        let now = to_sec(env::block_timestamp());
+        let staged_code = StagedCode {
+            code_hash: Base58CryptoHash::from(code_hash),
+            staged_at: now,
+            activation_time: now + self.upgrade_timelock,
+        };
+        self.staged_code.set(&code);
+        self.staged_code_info = Some(staged_code.clone());
+
+        NearEvent::log_code_stage(
+            env::predecessor_account_id(),
+            String::from(&staged_code.code_hash),
+            staged_code.activation_time,
+        );
+
+        self.internal_charge_storage(
+            env::storage_usage().saturating_sub(initial_storage_usage),
+            0,
+        );
+
+        staged_code
+    }
+
+    /// Deploys the staged code to this account and calls `migrate` on it
+    #[payable]
+    pub fn deploy_staged_code(&mut self) -> Promise {
+        assert_one_yocto();
+        self.assert_role(Role::Upgrader);
+        let staged_code = self
+            .staged_code_info
+            .take()
+            .expect("Nearlend Dao no staged code");
+        assert!(
+            to_sec(env::block_timestamp()) >= staged_code.activation_time,
+            "Nearlend Dao staged code is timelocked until {}",
+            staged_code.activation_time
+        );
+
+        let code = self.staged_code.take().unwrap();
+        let migrate_gas = env::prepaid_gas()
+            .saturating_sub(env::used_gas())
+            .saturating_sub(GAS_FOR_DEPLOY_STAGED_CODE);
+        assert!(
+            migrate_gas >= MIN_GAS_FOR_MIGRATE,
+            "Nearlend Dao not enough gas to migrate"
+        );
+
+        NearEvent::log_code_deploy(
+            env::predecessor_account_id(),
+            String::from(&staged_code.code_hash),
+        );
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(b"migrate".to_vec(), vec![], NO_DEPOSIT, migrate_gas)
+    }
+
+    #[payable]
+    pub fn set_upgrade_timelock(&mut self, upgrade_timelock: TimestampSec) {
+        assert_one_yocto();
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.tokens.owner_id,
+            "Nearlend Dao Owner only"
+        );
+        assert!(
+            upgrade_timelock >= MIN_UPGRADE_TIMELOCK,
+            "Nearlend Dao upgrade_timelock is less than {}",
+            MIN_UPGRADE_TIMELOCK
+        );
+        let old_upgrade_timelock = std::mem::replace(&mut self.upgrade_timelock, upgrade_timelock);
+
+        NearEvent::log_upgrade_timelock_update(
+            env::predecessor_account_id(),
+            old_upgrade_timelock,
+            upgrade_timelock,
+        );
+    }
+
+    pub fn get_staged_code(&self) -> Option<StagedCode> {
+        self.staged_code_info.clone()
+    }
+
+    pub fn get_upgrade_timelock(&self) -> TimestampSec {
+        self.upgrade_timelock
+    }
+
+    // Pause
+    #[payable]
+    pub fn set_paused(&mut self, operation: PauseOperation, paused: bool) {
+        assert_one_yocto();
+        self.assert_role(Role::Pauser);
+        assert_ne!(
+            self.paused.is_paused(operation),
+            paused,
+            "Nearlend Dao pause state unchanged"
+        );
+
+        self.paused.set(operation, paused);
+
+        NearEvent::log_pause_update(
+            env::predecessor_account_id(),
+            operation.as_str().to_string(),
+            paused,
+        );
+    }
+
+    pub fn get_paused(&self) -> &PauseState {
+        &self.paused
+    }
+
+    /// The single edition of a series on auction only goes to the winning bid
+    fn assert_not_on_auction(&self, token_series_id: &TokenSeriesId) {
+        assert!(
+            self.auctions.get(token_series_id).is_none(),
+            "Nearlend Dao series is on auction"
+        );
+    }
+
+    fn assert_not_paused(&self, operation: PauseOperation) {
+        assert!(
+            !self.paused.is_paused(operation),
+            "Nearlend Dao {} is paused",
+            operation.as_str()
+        );
+    }
+
+    // Roles
+    #[payable]
+    pub fn grant_role(&mut self, role: Role, account_id: ValidAccountId) {
+        assert!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let authorized_id = self.assert_role_admin(role);
+
+        let mut members = self
+            .roles
+            .get(&role)
+            .unwrap_or_else(|| UnorderedSet::new(StorageKey::RoleMembers { role }));
+        assert!(
+            members.insert(account_id.as_ref()),
+            "Nearlend Dao account already has role"
+        );
+        self.roles.insert(&role, &members);
+
+        NearEvent::log_role_grant(
+            authorized_id,
+            role.as_str().to_string(),
+            vec![account_id.to_string()],
+        );
+
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
+    }
+
+    #[payable]
+    pub fn revoke_role(&mut self, role: Role, account_id: ValidAccountId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let authorized_id = self.assert_role_admin(role);
+
+        let mut members = self
+            .roles
+            .get(&role)
+            .expect("Nearlend Dao account does not have role");
+        assert!(
+            members.remove(account_id.as_ref()),
+            "Nearlend Dao account does not have role"
+        );
+        self.roles.insert(&role, &members);
+
+        let storage_freed = initial_storage_usage.saturating_sub(env::storage_usage());
+        self.internal_refund_storage(&authorized_id, storage_freed);
+
+        NearEvent::log_role_revoke(
+            authorized_id,
+            role.as_str().to_string(),
+            vec![account_id.to_string()],
+        );
+    }
+
+    pub fn has_role(&self, role: Role, account_id: ValidAccountId) -> bool {
+        self.internal_has_role(role, account_id.as_ref())
+    }
+
+    pub fn get_role_members(
+        &self,
+        role: Role,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AccountId> {
+        let members = match self.roles.get(&role) {
+            Some(members) => members,
+            None => return vec![],
+        };
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+
+        members
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .collect()
+    }
+
+    pub fn get_account_roles(&self, account_id: ValidAccountId) -> Vec<Role> {
+        Role::ALL
+            .iter()
+            .filter(|role| self.internal_has_role(**role, account_id.as_ref()))
+            .copied()
+            .collect()
+    }
+
+    fn internal_has_role(&self, role: Role, account_id: &AccountId) -> bool {
+        self.roles
+            .get(&role)
+            .is_some_and(|members| members.contains(account_id))
+    }
+
+    /// Owner and admins pass every role check
+    fn assert_role(&self, role: Role) {
+        let sender_id = env::predecessor_account_id();
+        assert!(
+            sender_id == self.tokens.owner_id
+                || self.internal_has_role(Role::Admin, &sender_id)
+                || self.internal_has_role(role, &sender_id),
+            "Nearlend Dao {} role only",
+            role.as_str()
+        );
+    }
+
+    /// Admin role is managed by the owner only, other roles by the owner or an admin
+    fn assert_role_admin(&self, role: Role) -> AccountId {
+        let sender_id = env::predecessor_account_id();
+        if sender_id == self.tokens.owner_id {
+            return sender_id;
+        }
+        assert!(
+            role != Role::Admin && self.internal_has_role(Role::Admin, &sender_id),
+            "Nearlend Dao Owner only"
+        );
+        sender_id
+    }
+
+    // CUSTOM
+
+    #[payable]
+    pub fn nft_create_series(
+        &mut self,
+        creator_id: Option<ValidAccountId>,
+        token_metadata: TokenMetadata,
+        price: Option<U128>,
+        royalty: Option<HashMap<AccountId, u32>>,
+        sale: Option<SaleConfig>,
+    ) -> TokenSeriesJson {
+        self.assert_not_paused(PauseOperation::CreateSeries);
+        let initial_storage_usage = env::storage_usage();
+        let caller_id = env::predecessor_account_id();
+
+        if creator_id.is_some() {
+            assert_eq!(
+                creator_id.unwrap().to_string(),
+                caller_id,
+                "Nearlend Dao Caller is not creator_id"
+            );
+        }
+
+        let token_series_id = format!("{}", (self.token_series_by_id.len() + 1));
+
+        assert!(
+            self.token_series_by_id.get(&token_series_id).is_none(),
+            "Nearlend Dao duplicate token_series_id"
+        );
+
+        let title = token_metadata.title.clone();
+        assert!(
+            title.is_some(),
+            "Nearlend Dao token_metadata.title is required"
         );
 
         let mut total_perpetual = 0;
@@ -382,23 +1474,49 @@ impl Contract {
             None
         };
 
-        self.token_series_by_id.insert(
-            &token_series_id,
-            &TokenSeries {
-                metadata: token_metadata.clone(),
-                creator_id: caller_id.to_string(),
-                tokens: UnorderedSet::new(
-                    StorageKey::TokensBySeriesInner {
-                        token_series: token_series_id.clone(),
-                    }
-                    .try_to_vec()
-                    .unwrap(),
-                ),
-                price: price_res,
-                is_mintable: true,
-                royalty: royalty_res.clone(),
-            },
-        );
+        let SaleConfig {
+            ft_token_id,
+            sale_start,
+            sale_end,
+            max_per_account,
+        } = sale.unwrap_or_default();
+        let ft_token_id: Option<AccountId> = ft_token_id.map(|id| id.to_string());
+        if ft_token_id.is_some() {
+            assert_ne!(
+                price_res,
+                Some(0),
+                "Nearlend Dao price must be greater than 0"
+            );
+        }
+
+        assert_valid_sale_window(sale_start, sale_end);
+
+        let token_series = TokenSeries {
+            metadata: token_metadata.clone(),
+            creator_id: caller_id.to_string(),
+            tokens: UnorderedSet::new(
+                StorageKey::TokensBySeriesInner {
+                    token_series: token_series_id.clone(),
+                }
+                .try_to_vec()
+                .unwrap(),
+            ),
+            price: price_res,
+            is_mintable: true,
+            royalty: royalty_res.clone(),
+            ft_token_id: ft_token_id.clone(),
+            public_sale_start: None,
+            sale_start,
+            sale_end,
+            max_per_account,
+            price_strategy: None,
+            burned: 0,
+            reveal: None,
+            unminted_editions: None,
+            is_metadata_frozen: false,
+        };
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
 
         // set market data transaction fee
         let current_transaction_fee = self.calculate_current_transaction_fee();
@@ -406,31 +1524,24 @@ impl Contract {
             .transaction_fee
             .insert(&token_series_id, &current_transaction_fee);
 
-        env::log(
-            json!({
-                "type": "nft_create_series",
-                "params": {
-                    "token_series_id": token_series_id,
-                    "token_metadata": token_metadata,
-                    "creator_id": caller_id,
-                    "price": price,
-                    "royalty": royalty_res,
-                    "transaction_fee": &current_transaction_fee.to_string()
-                }
-            })
-            .to_string()
-            .as_bytes(),
-        );
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesCreate(vec![
+            SeriesCreateData {
+                token_series_id: token_series_id.clone(),
+                creator_id: caller_id.to_string(),
+                token_metadata,
+                price: price_res.map(|price| price.to_string()),
+                royalty: royalty_res,
+                transaction_fee: current_transaction_fee.to_string(),
+                ft_token_id,
+                sale_start,
+                sale_end,
+                max_per_account,
+            },
+        ]));
 
-        refund_deposit(env::storage_usage() - initial_storage_usage, 0);
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
 
-        TokenSeriesJson {
-            token_series_id,
-            metadata: token_metadata,
-            creator_id: caller_id.into(),
-            royalty: royalty_res,
-            transaction_fee: Some(current_transaction_fee.into()),
-        }
+        token_series.into_json(token_series_id, Some(current_transaction_fee.into()))
     }
 
     #[payable]
@@ -445,15 +1556,32 @@ impl Contract {
             .token_series_by_id
             .get(&token_series_id)
             .expect("Nearlend Dao Token series not exist");
-        let price: u128 = token_series.price.expect("Nearlend Dao not for sale");
+        if let Some(ft_token_id) = &token_series.ft_token_id {
+            env::panic(
+                format!(
+                    "Nearlend Dao series is priced in {}, buy with ft_transfer_call",
+                    ft_token_id
+                )
+                .as_bytes(),
+            );
+        }
+        let price: u128 = self.internal_buy_price(
+            &token_series_id,
+            &token_series,
+            &env::predecessor_account_id(),
+        );
         let attached_deposit = env::attached_deposit();
         assert!(
             attached_deposit >= price,
             "Nearlend Dao attached deposit is less than price : {}",
             price
         );
-        let token_id: TokenId =
-            self._nft_mint_series(token_series_id.clone(), receiver_id.to_string());
+        let token_id: TokenId = self._nft_mint_series(
+            token_series_id.clone(),
+            receiver_id.to_string(),
+            None,
+            &env::predecessor_account_id(),
+        );
 
         let for_treasury = price as u128
             * self.calculate_market_data_transaction_fee(&token_series_id)
@@ -465,7 +1593,7 @@ impl Contract {
             Promise::new(self.treasury_id.clone()).transfer(for_treasury);
         }
 
-        refund_deposit(env::storage_usage() - initial_storage_usage, price);
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, price);
 
         NearEvent::log_nft_mint(
             receiver_id.to_string(),
@@ -476,11 +1604,13 @@ impl Contract {
         token_id
     }
 
+    /// `edition_metadata` takes precedence over the metadata set with `nft_set_edition_metadata`
     #[payable]
     pub fn nft_mint(
         &mut self,
         token_series_id: TokenSeriesId,
         receiver_id: ValidAccountId,
+        edition_metadata: Option<EditionMetadata>,
     ) -> TokenId {
         let initial_storage_usage = env::storage_usage();
 
@@ -493,22 +1623,33 @@ impl Contract {
             token_series.creator_id,
             "Nearlend Dao not creator"
         );
-        let token_id: TokenId = self._nft_mint_series(token_series_id, receiver_id.to_string());
+        self.assert_not_on_auction(&token_series_id);
+        if let Some(edition_metadata) = &edition_metadata {
+            edition_metadata.assert_valid();
+        }
+        let token_id: TokenId = self._nft_mint_series(
+            token_series_id,
+            receiver_id.to_string(),
+            edition_metadata,
+            &env::predecessor_account_id(),
+        );
 
-        refund_deposit(env::storage_usage() - initial_storage_usage, 0);
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
 
         NearEvent::log_nft_mint(receiver_id.to_string(), vec![token_id.clone()], None);
 
         token_id
     }
 
+    /// Mints `count` tokens to every receiver in a single call. Stops before a token that doesn't
+    /// fit in the remaining gas, call again with the receivers from `processed` and the count of
+    /// that receiver lowered by `partial_count`.
     #[payable]
-    pub fn nft_mint_and_approve(
+    pub fn nft_mint_batch(
         &mut self,
         token_series_id: TokenSeriesId,
-        account_id: ValidAccountId,
-        msg: Option<String>,
-    ) -> Option<Promise> {
+        receivers: Vec<BatchMintReceiver>,
+    ) -> BatchMintJson {
         let initial_storage_usage = env::storage_usage();
 
         let token_series = self
@@ -520,26 +1661,380 @@ impl Contract {
             token_series.creator_id,
             "Nearlend Dao not creator"
         );
-        let token_id: TokenId =
-            self._nft_mint_series(token_series_id, token_series.creator_id.clone());
+        self.assert_not_on_auction(&token_series_id);
+        assert!(!receivers.is_empty(), "Nearlend Dao receivers is empty");
+        assert!(
+            receivers.iter().all(|receiver| receiver.count != Some(0)),
+            "Nearlend Dao count must be positive"
+        );
+        if let Some(copies) = token_series.metadata.copies {
+            let remaining = copies.saturating_sub(token_series.minted());
+            let total: u64 = receivers
+                .iter()
+                .map(|receiver| receiver.count.unwrap_or(1) as u64)
+                .sum();
+            assert!(
+                total <= remaining,
+                "Nearlend Dao batch is more than the remaining copies : {}",
+                remaining
+            );
+        }
 
-        // Need to copy the nft_approve code here to solve the gas problem
-        // get contract-level LookupMap of token_id to approvals HashMap
-        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let mut mints: Vec<NftMintData> = vec![];
+        let mut processed: u32 = 0;
+        let mut partial_count: u32 = 0;
+        'receivers: for receiver in &receivers {
+            let mut token_ids: Vec<TokenId> = vec![];
+            for _ in 0..receiver.count.unwrap_or(1) {
+                if env::prepaid_gas() - env::used_gas()
+                    < GAS_FOR_BATCH_MINT_FINISH + GAS_FOR_BATCH_MINT_TOKEN
+                {
+                    partial_count = token_ids.len() as u32;
+                    if !token_ids.is_empty() {
+                        mints.push(NftMintData {
+                            owner_id: receiver.receiver_id.to_string(),
+                            token_ids,
+                            memo: None,
+                        });
+                    }
+                    break 'receivers;
+                }
+                token_ids.push(self._nft_mint_series(
+                    token_series_id.clone(),
+                    receiver.receiver_id.to_string(),
+                    None,
+                    &token_series.creator_id,
+                ));
+            }
+            mints.push(NftMintData {
+                owner_id: receiver.receiver_id.to_string(),
+                token_ids,
+                memo: None,
+            });
+            processed += 1;
+        }
+        assert!(
+            !mints.is_empty(),
+            "Nearlend Dao not enough gas to mint the first token"
+        );
 
-        // update HashMap of approvals for this token
-        let approved_account_ids = &mut approvals_by_id
-            .get(&token_id)
-            .unwrap_or_else(|| HashMap::new());
-        let account_id: AccountId = account_id.into();
-        let approval_id: u64 = self
-            .tokens
-            .next_approval_id_by_id
-            .as_ref()
-            .unwrap()
-            .get(&token_id)
-            .unwrap_or_else(|| 1u64);
-        approved_account_ids.insert(account_id.clone(), approval_id);
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
+        let token_ids: Vec<TokenId> = mints
+            .iter()
+            .flat_map(|mint| mint.token_ids.iter().cloned())
+            .collect();
+        NearEvent::log_nft_mints(mints);
+
+        BatchMintJson {
+            token_ids,
+            processed,
+            partial_count,
+            completed: processed as usize == receivers.len(),
+        }
+    }
+
+    /// Sets the metadata of editions that are not minted yet, used when they are minted
+    #[payable]
+    pub fn nft_set_edition_metadata(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        editions: Vec<EditionMetadataEntry>,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao not creator"
+        );
+
+        for entry in editions {
+            entry.metadata.assert_valid();
+            assert!(entry.edition > 0, "Nearlend Dao edition starts at 1");
+            if let Some(copies) = token_series.metadata.copies {
+                assert!(
+                    entry.edition <= copies,
+                    "Nearlend Dao edition is more than copies : {}",
+                    copies
+                );
+            }
+            let token_id = format!("{}{}{}", &token_series_id, TOKEN_DELIMETER, entry.edition);
+            assert!(
+                !token_series.is_edition_minted(entry.edition, &token_id),
+                "Nearlend Dao edition {} is already minted",
+                entry.edition
+            );
+            self.edition_metadata.insert(&token_id, &entry.metadata);
+        }
+
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        self.internal_charge_storage(storage_used, 0);
+    }
+
+    /// Commits to the final edition metadata before the first mint, revealed with
+    /// `nft_reveal_series_manifest` or `nft_reveal_series_cid` once the sale ends
+    #[payable]
+    pub fn nft_set_series_reveal_commitment(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        commitment: Base58CryptoHash,
+    ) {
+        assert_one_yocto();
+
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+        assert_eq!(
+            token_series.minted(),
+            0,
+            "Nearlend Dao reveal commitment must be set before the first mint"
+        );
+
+        token_series.reveal = Some(SeriesReveal {
+            commitment: commitment.into(),
+            revealed: None,
+        });
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetRevealCommitment(vec![
+            SeriesSetRevealCommitmentData {
+                token_series_id,
+                commitment: String::from(&commitment),
+            },
+        ]));
+    }
+
+    /// Draws the edition of each mint at random from the unminted ones instead of in order,
+    /// only before the first mint and for a series with copies
+    #[payable]
+    pub fn nft_set_series_random_editions(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        is_random: bool,
+    ) {
+        assert_one_yocto();
+
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+        assert_eq!(
+            token_series.minted(),
+            0,
+            "Nearlend Dao random editions must be set before the first mint"
+        );
+        assert!(
+            token_series.metadata.copies.is_some(),
+            "Nearlend Dao random editions require copies"
+        );
+
+        token_series.unminted_editions = if is_random {
+            Some(LookupMap::new(
+                StorageKey::UnmintedEditionsInner {
+                    token_series: token_series_id.clone(),
+                }
+                .try_to_vec()
+                .unwrap(),
+            ))
+        } else {
+            None
+        };
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetRandomEditions(vec![
+            SeriesSetRandomEditionsData {
+                token_series_id,
+                is_random,
+            },
+        ]));
+    }
+
+    /// Reveals a JSON array of `EditionMetadata`, the first entry is edition 1. Minted editions
+    /// are updated in place, the others get their metadata when minted.
+    #[payable]
+    pub fn nft_reveal_series_manifest(&mut self, token_series_id: TokenSeriesId, manifest: String) {
+        let initial_storage_usage = env::storage_usage();
+
+        let mut token_series =
+            self.internal_assert_revealable(&token_series_id, env::sha256(manifest.as_bytes()));
+        let editions: Vec<EditionMetadata> =
+            near_sdk::serde_json::from_str(&manifest).expect("Nearlend Dao invalid manifest");
+        let minted = token_series.minted();
+        assert!(
+            editions.len() as u64 >= minted,
+            "Nearlend Dao manifest must have an entry for every minted edition"
+        );
+
+        // a series closed before selling out only reveals its minted editions
+        let token_metadata_by_id = self.tokens.token_metadata_by_id.as_mut().unwrap();
+        for (index, edition_metadata) in editions.into_iter().take(minted as usize).enumerate() {
+            let token_id = format!("{}{}{}", &token_series_id, TOKEN_DELIMETER, index + 1);
+            // burned editions have no metadata left to update
+            if let Some(mut token_metadata) = token_metadata_by_id.get(&token_id) {
+                apply_edition_metadata(&mut token_metadata, edition_metadata);
+                token_metadata_by_id.insert(&token_id, &token_metadata);
+            }
+        }
+
+        token_series.reveal.as_mut().unwrap().revealed = Some(RevealedMetadata::Manifest);
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        self.internal_charge_storage(storage_used, 0);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesReveal(vec![
+            SeriesRevealData {
+                token_series_id,
+                cid: None,
+                seed: None,
+            },
+        ]));
+    }
+
+    /// Reveals the CID of a directory with one `{index}.json` per edition, editions are mapped
+    /// to an index shifted by the sha256 of `seed`
+    #[payable]
+    pub fn nft_reveal_series_cid(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        cid: String,
+        seed: String,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+
+        let mut token_series = self.internal_assert_revealable(
+            &token_series_id,
+            env::sha256(format!("{}:{}", cid, seed).as_bytes()),
+        );
+        let editions = token_series
+            .metadata
+            .copies
+            .unwrap_or_else(|| token_series.minted());
+        assert!(editions > 0, "Nearlend Dao no editions to reveal");
+        let seed_hash = env::sha256(seed.as_bytes());
+        let offset = u64::from_le_bytes(seed_hash[..8].try_into().unwrap()) % editions;
+
+        token_series.reveal.as_mut().unwrap().revealed = Some(RevealedMetadata::Cid {
+            cid: cid.clone(),
+            offset,
+            editions,
+        });
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        self.internal_charge_storage(storage_used, 0);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesReveal(vec![
+            SeriesRevealData {
+                token_series_id,
+                cid: Some(cid),
+                seed: Some(seed),
+            },
+        ]));
+    }
+
+    /// Checks the caller, that minting is closed and `hash` against the commitment of the series
+    fn internal_assert_revealable(
+        &self,
+        token_series_id: &TokenSeriesId,
+        hash: Vec<u8>,
+    ) -> TokenSeries {
+        let token_series = self
+            .token_series_by_id
+            .get(token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+        let reveal = token_series
+            .reveal
+            .as_ref()
+            .expect("Nearlend Dao series has no reveal commitment");
+        assert!(
+            reveal.revealed.is_none(),
+            "Nearlend Dao series is already revealed"
+        );
+        // the creator can still mint after sale_end, editions are only final once non mintable
+        assert!(
+            !token_series.is_mintable,
+            "Nearlend Dao series is still mintable"
+        );
+        assert!(
+            hash == reveal.commitment,
+            "Nearlend Dao reveal does not match the commitment"
+        );
+        token_series
+    }
+
+    #[payable]
+    pub fn nft_mint_and_approve(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        account_id: ValidAccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        assert!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao not creator"
+        );
+        self.assert_not_on_auction(&token_series_id);
+        let token_id: TokenId = self._nft_mint_series(
+            token_series_id,
+            token_series.creator_id.clone(),
+            None,
+            &token_series.creator_id,
+        );
+
+        // Need to copy the nft_approve code here to solve the gas problem
+        // get contract-level LookupMap of token_id to approvals HashMap
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+
+        // update HashMap of approvals for this token
+        let approved_account_ids = &mut approvals_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| HashMap::new());
+        let account_id: AccountId = account_id.into();
+        let approval_id: u64 = self
+            .tokens
+            .next_approval_id_by_id
+            .as_ref()
+            .unwrap()
+            .get(&token_id)
+            .unwrap_or_else(|| 1u64);
+        approved_account_ids.insert(account_id.clone(), approval_id);
 
         // save updated approvals HashMap to contract's LookupMap
         approvals_by_id.insert(&token_id, &approved_account_ids);
@@ -551,7 +2046,7 @@ impl Contract {
             .unwrap()
             .insert(&token_id, &(approval_id + 1));
 
-        refund_deposit(env::storage_usage() - initial_storage_usage, 0);
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
 
         NearEvent::log_nft_mint(
             token_series.creator_id.clone(),
@@ -574,11 +2069,69 @@ impl Contract {
         }
     }
 
+    /// Price the buyer pays for the next token, consuming presale quota while the presale is running
+    /// and the buyer's max_per_account allowance
+    fn internal_buy_price(
+        &mut self,
+        token_series_id: &TokenSeriesId,
+        token_series: &TokenSeries,
+        buyer_id: &AccountId,
+    ) -> Balance {
+        let now = to_sec(env::block_timestamp());
+        assert!(
+            token_series.is_in_sale_window(now),
+            "Nearlend Dao sale is not open"
+        );
+        self.assert_not_on_auction(token_series_id);
+
+        if let Some(max_per_account) = token_series.max_per_account {
+            let key = series_account_key(token_series_id, buyer_id);
+            let minted = self.minted_per_account.get(&key).unwrap_or(0);
+            assert!(
+                minted < max_per_account,
+                "Nearlend Dao max per account reached : {}",
+                max_per_account
+            );
+            self.minted_per_account.insert(&key, &(minted + 1));
+        }
+
+        let is_presale = token_series
+            .public_sale_start
+            .is_some_and(|start| now < start);
+        if !is_presale {
+            return token_series
+                .current_price(now)
+                .expect("Nearlend Dao not for sale");
+        }
+
+        let key = series_account_key(token_series_id, buyer_id);
+        let mut allowance = self
+            .presale_allowlist
+            .get(&key)
+            .expect("Nearlend Dao presale is only for allowlisted accounts");
+        assert!(
+            allowance.minted < allowance.quota,
+            "Nearlend Dao presale quota exceeded : {}",
+            allowance.quota
+        );
+        allowance.minted += 1;
+        self.presale_allowlist.insert(&key, &allowance);
+
+        allowance
+            .price
+            .or_else(|| token_series.current_price(now))
+            .expect("Nearlend Dao not for sale")
+    }
+
+    /// `storage_payer_id` is the account charged for the token storage by the caller
     fn _nft_mint_series(
         &mut self,
         token_series_id: TokenSeriesId,
         receiver_id: AccountId,
+        edition_metadata: Option<EditionMetadata>,
+        storage_payer_id: &AccountId,
     ) -> TokenId {
+        self.assert_not_paused(PauseOperation::Mint);
         let mut token_series = self
             .token_series_by_id
             .get(&token_series_id)
@@ -588,7 +2141,7 @@ impl Contract {
             "Nearlend Dao Token series is not mintable"
         );
 
-        let num_tokens = token_series.tokens.len();
+        let num_tokens = token_series.minted();
         let max_copies = token_series.metadata.copies.unwrap_or(u64::MAX);
         assert!(num_tokens < max_copies, "Series supply maxed");
 
@@ -596,13 +2149,16 @@ impl Contract {
             token_series.is_mintable = false;
         }
 
-        let token_id = format!("{}{}{}", &token_series_id, TOKEN_DELIMETER, num_tokens + 1);
+        let edition = token_series.next_edition();
+        let token_id = format!("{}{}{}", &token_series_id, TOKEN_DELIMETER, edition);
         token_series.tokens.insert(&token_id);
         self.token_series_by_id
             .insert(&token_series_id, &token_series);
 
-        // you can add custom metadata to each token here
-        let metadata = Some(TokenMetadata {
+        // edition metadata given to the mint, or set by the creator beforehand
+        let precommitted_metadata = self.edition_metadata.remove(&token_id);
+
+        let mut metadata = Some(TokenMetadata {
             title: None,       // ex. "Arch Nemesis: Mail Carrier" or "Parcel #5055"
             description: None, // free-form description
             media: None, // URL to associated media, preferably to decentralized, content-addressed storage
@@ -616,6 +2172,9 @@ impl Contract {
             reference: None, // URL to an off-chain JSON file with more info.
             reference_hash: None, // Base64-encoded sha256 hash of JSON from reference field. Required if `reference` is included.
         });
+        if let Some(edition_metadata) = edition_metadata.or(precommitted_metadata) {
+            apply_edition_metadata(metadata.as_mut().unwrap(), edition_metadata);
+        }
 
         //let token = self.tokens.mint(token_id, receiver_id, metadata);
         // From : https://github.com/near/near-sdk-rs/blob/master/near-contract-standards/src/non_fungible_token/core/core_impl.rs#L359
@@ -623,6 +2182,7 @@ impl Contract {
 
         let owner_id: AccountId = receiver_id;
         self.tokens.owner_by_id.insert(&token_id, &owner_id);
+        self.storage_payers.insert(&token_id, storage_payer_id);
 
         self.tokens
             .token_metadata_by_id
@@ -643,10 +2203,17 @@ impl Contract {
     }
 
     #[payable]
-    pub fn nft_set_series_non_mintable(&mut self, token_series_id: TokenSeriesId) {
-        assert_one_yocto();
+    pub fn nft_create_auction(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        reserve_price: U128,
+        min_bid_increment: U128,
+        end_time: TimestampSec,
+        extension: u32,
+    ) -> AuctionJson {
+        let initial_storage_usage = env::storage_usage();
 
-        let mut token_series = self
+        let token_series = self
             .token_series_by_id
             .get(&token_series_id)
             .expect("Token series not exist");
@@ -655,52 +2222,270 @@ impl Contract {
             token_series.creator_id,
             "Nearlend Dao Creator only"
         );
-
         assert_eq!(
-            token_series.is_mintable, true,
-            "Nearlend Dao already non-mintable"
+            token_series.metadata.copies,
+            Some(1),
+            "Nearlend Dao auction requires a single edition series"
         );
-
-        assert_eq!(
-            token_series.metadata.copies, None,
-            "Nearlend Dao decrease supply if copies not null"
+        assert!(
+            token_series.is_mintable && token_series.minted() == 0,
+            "Nearlend Dao Token series is not mintable"
         );
-
-        token_series.is_mintable = false;
-        self.token_series_by_id
-            .insert(&token_series_id, &token_series);
-        env::log(
-            json!({
-                "type": "nft_set_series_non_mintable",
-                "params": {
-                    "token_series_id": token_series_id,
-                }
-            })
-            .to_string()
-            .as_bytes(),
+        assert!(
+            token_series.ft_token_id.is_none(),
+            "Nearlend Dao auction bids are in NEAR only"
         );
+        self.assert_not_on_auction(&token_series_id);
+        assert!(
+            reserve_price.0 > 0,
+            "Nearlend Dao reserve_price must be positive"
+        );
+        assert!(
+            reserve_price.0 < MAX_PRICE,
+            "Nearlend Dao price higher than {}",
+            MAX_PRICE
+        );
+        assert!(
+            min_bid_increment.0 > 0,
+            "Nearlend Dao min_bid_increment must be positive"
+        );
+        assert!(
+            end_time > to_sec(env::block_timestamp()),
+            "end_time is less than current block_timestamp"
+        );
+
+        let auction = Auction {
+            reserve_price: reserve_price.0,
+            min_bid_increment: min_bid_increment.0,
+            end_time,
+            extension,
+            bidder_id: None,
+            bid: 0,
+        };
+        self.auctions.insert(&token_series_id, &auction);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::AuctionCreate(vec![
+            AuctionCreateData {
+                token_series_id: token_series_id.clone(),
+                reserve_price: reserve_price.0.to_string(),
+                min_bid_increment: min_bid_increment.0.to_string(),
+                end_time,
+                extension,
+            },
+        ]));
+
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
+        auction.to_json(token_series_id)
     }
 
+    /// Attached deposit is the bid, the previous highest bidder is refunded
     #[payable]
-    pub fn nft_decrease_series_copies(
-        &mut self,
-        token_series_id: TokenSeriesId,
-        decrease_copies: U64,
-    ) -> U64 {
-        assert_one_yocto();
+    pub fn nft_auction_bid(&mut self, token_series_id: TokenSeriesId) -> AuctionJson {
+        let bidder_id = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        let now = to_sec(env::block_timestamp());
 
-        let mut token_series = self
-            .token_series_by_id
+        let mut auction = self
+            .auctions
             .get(&token_series_id)
-            .expect("Token series not exist");
-        assert_eq!(
-            env::predecessor_account_id(),
-            token_series.creator_id,
-            "Nearlend Dao Creator only"
-        );
+            .expect("Nearlend Dao auction not exist");
+        assert!(now < auction.end_time, "Nearlend Dao auction has ended");
 
-        let minted_copies = token_series.tokens.len();
-        let copies = token_series.metadata.copies.unwrap();
+        let creator_id = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist")
+            .creator_id;
+        assert_ne!(bidder_id, creator_id, "Nearlend Dao creator cannot bid");
+
+        let min_bid = match auction.bidder_id {
+            Some(_) => auction.bid + auction.min_bid_increment,
+            None => auction.reserve_price,
+        };
+        assert!(
+            amount >= min_bid,
+            "Nearlend Dao bid is less than minimum bid : {}",
+            min_bid
+        );
+
+        if let Some(previous_bidder_id) = auction.bidder_id.take() {
+            Promise::new(previous_bidder_id).transfer(auction.bid);
+        }
+        auction.bidder_id = Some(bidder_id.clone());
+        auction.bid = amount;
+
+        if auction.end_time - now < auction.extension {
+            auction.end_time = now + auction.extension;
+        }
+        self.auctions.insert(&token_series_id, &auction);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::AuctionBid(vec![AuctionBidData {
+            token_series_id: token_series_id.clone(),
+            bidder_id,
+            bid: amount.to_string(),
+            end_time: auction.end_time,
+        }]));
+
+        auction.to_json(token_series_id)
+    }
+
+    /// Anyone can settle after end_time. Storage for the minted token is taken from the winning bid,
+    /// the bid is refunded instead when the token can no longer be minted, e.g. minting is paused.
+    pub fn nft_settle_auction(&mut self, token_series_id: TokenSeriesId) -> Option<TokenId> {
+        let initial_storage_usage = env::storage_usage();
+
+        let auction = self
+            .auctions
+            .get(&token_series_id)
+            .expect("Nearlend Dao auction not exist");
+        assert!(
+            to_sec(env::block_timestamp()) >= auction.end_time,
+            "Nearlend Dao auction has not ended"
+        );
+        self.auctions.remove(&token_series_id);
+
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        // e.g. minting paused or the series made non mintable while the auction was running
+        let is_mintable = token_series.is_mintable
+            && token_series.minted() < token_series.metadata.copies.unwrap_or(u64::MAX)
+            && !self.paused.is_paused(PauseOperation::Mint);
+
+        let token_id: Option<TokenId> = match auction.bidder_id {
+            Some(bidder_id) if is_mintable => {
+                let creator_id = token_series.creator_id;
+                let token_id: TokenId = self._nft_mint_series(
+                    token_series_id.clone(),
+                    bidder_id.clone(),
+                    None,
+                    &bidder_id,
+                );
+
+                let storage_cost = env::storage_byte_cost()
+                    * Balance::from(env::storage_usage().saturating_sub(initial_storage_usage));
+                let proceeds = auction.bid.saturating_sub(storage_cost);
+                let for_treasury = proceeds
+                    * self.calculate_market_data_transaction_fee(&token_series_id)
+                    / 10_000u128;
+                Promise::new(creator_id).transfer(proceeds - for_treasury);
+
+                if for_treasury != 0 {
+                    Promise::new(self.treasury_id.clone()).transfer(for_treasury);
+                }
+
+                NearEvent::log_nft_mint(
+                    bidder_id,
+                    vec![token_id.clone()],
+                    Some(json!({"price": auction.bid.to_string()}).to_string()),
+                );
+
+                Some(token_id)
+            }
+            Some(bidder_id) => {
+                Promise::new(bidder_id).transfer(auction.bid);
+                None
+            }
+            None => None,
+        };
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::AuctionSettle(vec![
+            AuctionSettleData {
+                token_series_id,
+                token_id: token_id.clone(),
+            },
+        ]));
+
+        token_id
+    }
+
+    #[payable]
+    pub fn nft_cancel_auction(&mut self, token_series_id: TokenSeriesId) {
+        assert_one_yocto();
+
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+
+        let auction = self
+            .auctions
+            .get(&token_series_id)
+            .expect("Nearlend Dao auction not exist");
+        assert!(
+            auction.bidder_id.is_none(),
+            "Nearlend Dao auction already has bids"
+        );
+        self.auctions.remove(&token_series_id);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::AuctionCancel(vec![SeriesData {
+            token_series_id,
+        }]));
+    }
+
+    #[payable]
+    pub fn nft_set_series_non_mintable(&mut self, token_series_id: TokenSeriesId) {
+        assert_one_yocto();
+
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        if env::predecessor_account_id() != token_series.creator_id {
+            self.assert_role(Role::SeriesCurator);
+        }
+
+        assert_eq!(
+            token_series.is_mintable, true,
+            "Nearlend Dao already non-mintable"
+        );
+
+        assert_eq!(
+            token_series.metadata.copies, None,
+            "Nearlend Dao decrease supply if copies not null"
+        );
+
+        token_series.is_mintable = false;
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetNonMintable(vec![
+            SeriesData { token_series_id },
+        ]));
+    }
+
+    #[payable]
+    pub fn nft_decrease_series_copies(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        decrease_copies: U64,
+    ) -> U64 {
+        assert_one_yocto();
+
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+        self.assert_not_on_auction(&token_series_id);
+
+        assert!(
+            token_series.unminted_editions.is_none(),
+            "Nearlend Dao cannot decrease supply of a random edition series"
+        );
+
+        let minted_copies = token_series.minted();
+        let copies = token_series.metadata.copies.unwrap();
 
         assert!(
             (copies - decrease_copies.0) >= minted_copies,
@@ -717,20 +2502,24 @@ impl Contract {
 
         token_series.metadata.copies = Some(copies - decrease_copies.0);
 
+        // metadata set for the editions that can no longer be minted, the creator paid for it
+        let initial_storage_usage = env::storage_usage();
+        for edition in (copies - decrease_copies.0 + 1)..=copies {
+            let token_id = format!("{}{}{}", &token_series_id, TOKEN_DELIMETER, edition);
+            self.edition_metadata.remove(&token_id);
+        }
+        let storage_freed = initial_storage_usage - env::storage_usage();
+        self.internal_refund_storage(&token_series.creator_id, storage_freed);
+
         self.token_series_by_id
             .insert(&token_series_id, &token_series);
-        env::log(
-            json!({
-                "type": "nft_decrease_series_copies",
-                "params": {
-                    "token_series_id": token_series_id,
-                    "copies": U64::from(token_series.metadata.copies.unwrap()),
-                    "is_non_mintable": is_non_mintable,
-                }
-            })
-            .to_string()
-            .as_bytes(),
-        );
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesDecreaseCopies(vec![
+            SeriesDecreaseCopiesData {
+                token_series_id,
+                copies: token_series.metadata.copies.unwrap(),
+                is_non_mintable,
+            },
+        ]));
         U64::from(token_series.metadata.copies.unwrap())
     }
 
@@ -757,6 +2546,13 @@ impl Contract {
             "Nearlend Dao token series is not mintable"
         );
 
+        if token_series.ft_token_id.is_some() {
+            assert!(
+                price.is_none_or(|price| price.0 > 0),
+                "Nearlend Dao price must be greater than 0"
+            );
+        }
+
         if price.is_none() {
             token_series.price = None;
         } else {
@@ -777,724 +2573,3908 @@ impl Contract {
             .transaction_fee
             .insert(&token_series_id, &current_transaction_fee);
 
-        env::log(
-            json!({
-                "type": "nft_set_series_price",
-                "params": {
-                    "token_series_id": token_series_id,
-                    "price": price,
-                    "transaction_fee": current_transaction_fee.to_string()
-                }
-            })
-            .to_string()
-            .as_bytes(),
-        );
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetPrice(vec![
+            SeriesSetPriceData {
+                token_series_id,
+                price: price.map(|price| price.0.to_string()),
+                transaction_fee: current_transaction_fee.to_string(),
+            },
+        ]));
         return price;
     }
 
+    /// Updates the series metadata until it is frozen, storage growth is paid by the caller
     #[payable]
-    pub fn nft_burn(&mut self, token_id: TokenId) {
-        assert_one_yocto();
+    pub fn nft_update_series_metadata(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        metadata: SeriesMetadataUpdate,
+    ) {
+        assert!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
 
-        let owner_id = self.tokens.owner_by_id.get(&token_id).unwrap();
-        assert_eq!(owner_id, env::predecessor_account_id(), "Token owner only");
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+        assert!(
+            !token_series.is_metadata_frozen,
+            "Nearlend Dao series metadata is frozen"
+        );
+        assert_valid_hash(&metadata.media_hash);
+        assert_valid_hash(&metadata.reference_hash);
 
-        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
-            next_approval_id_by_id.remove(&token_id);
+        let series_metadata = &mut token_series.metadata;
+        if metadata.description.is_some() {
+            series_metadata.description = metadata.description;
         }
-
-        if let Some(approvals_by_id) = &mut self.tokens.approvals_by_id {
-            approvals_by_id.remove(&token_id);
+        // a new media without its hash drops the previous hash
+        if metadata.media.is_some() {
+            series_metadata.media = metadata.media;
+            series_metadata.media_hash = None;
         }
-
-        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
-            let mut token_ids = tokens_per_owner.get(&owner_id).unwrap();
-            token_ids.remove(&token_id);
-            tokens_per_owner.insert(&owner_id, &token_ids);
+        if metadata.media_hash.is_some() {
+            series_metadata.media_hash = metadata.media_hash;
         }
-
-        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
-            token_metadata_by_id.remove(&token_id);
+        if metadata.reference.is_some() {
+            series_metadata.reference = metadata.reference;
+            series_metadata.reference_hash = None;
         }
+        if metadata.reference_hash.is_some() {
+            series_metadata.reference_hash = metadata.reference_hash;
+        }
+        if metadata.extra.is_some() {
+            series_metadata.extra = metadata.extra;
+        }
+        series_metadata.updated_at = Some(env::block_timestamp().to_string());
 
-        self.tokens.owner_by_id.remove(&token_id);
-
-        NearEvent::log_nft_burn(owner_id, vec![token_id], None, None);
-    }
-
-    // CUSTOM VIEWS
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
 
-    pub fn nft_get_series_single(&self, token_series_id: TokenSeriesId) -> TokenSeriesJson {
-        let token_series = self
-            .token_series_by_id
-            .get(&token_series_id)
-            .expect("Series does not exist");
-        let current_transaction_fee = self.get_market_data_transaction_fee(&token_series_id);
-        TokenSeriesJson {
-            token_series_id,
-            metadata: token_series.metadata,
-            creator_id: token_series.creator_id,
-            royalty: token_series.royalty,
-            transaction_fee: Some(current_transaction_fee.into()),
+        let final_storage_usage = env::storage_usage();
+        self.internal_charge_storage(final_storage_usage.saturating_sub(initial_storage_usage), 0);
+        if final_storage_usage < initial_storage_usage {
+            self.internal_refund_storage(
+                &env::predecessor_account_id(),
+                initial_storage_usage - final_storage_usage,
+            );
         }
-    }
 
-    pub fn nft_get_series_format(self) -> (char, &'static str, &'static str) {
-        (TOKEN_DELIMETER, TITLE_DELIMETER, EDITION_DELIMETER)
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesUpdateMetadata(vec![
+            SeriesUpdateMetadataData {
+                token_series_id,
+                token_metadata: token_series.metadata,
+            },
+        ]));
     }
 
-    pub fn nft_get_series_price(self, token_series_id: TokenSeriesId) -> Option<U128> {
-        let price = self.token_series_by_id.get(&token_series_id).unwrap().price;
-        match price {
-            Some(p) => return Some(U128::from(p)),
-            None => return None,
-        };
-    }
+    /// One way, `nft_update_series_metadata` is rejected afterwards
+    #[payable]
+    pub fn nft_freeze_series_metadata(&mut self, token_series_id: TokenSeriesId) {
+        assert_one_yocto();
 
-    pub fn nft_get_series(
-        &self,
-        from_index: Option<U128>,
-        limit: Option<u64>,
-    ) -> Vec<TokenSeriesJson> {
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
         assert!(
-            (self.token_series_by_id.len() as u128) > start_index,
-            "Out of bounds, please use a smaller from_index."
+            !token_series.is_metadata_frozen,
+            "Nearlend Dao series metadata is frozen"
         );
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
 
+        token_series.is_metadata_frozen = true;
         self.token_series_by_id
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|(token_series_id, token_series)| TokenSeriesJson {
-                token_series_id,
-                metadata: token_series.metadata,
-                creator_id: token_series.creator_id,
-                royalty: token_series.royalty,
-                transaction_fee: None,
-            })
-            .collect()
-    }
+            .insert(&token_series_id, &token_series);
 
-    pub fn nft_supply_for_series(&self, token_series_id: TokenSeriesId) -> U64 {
-        self.token_series_by_id
-            .get(&token_series_id)
-            .expect("Token series not exist")
-            .tokens
-            .len()
-            .into()
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesFreezeMetadata(vec![
+            SeriesData { token_series_id },
+        ]));
     }
 
-    pub fn nft_tokens_by_series(
-        &self,
+    #[payable]
+    pub fn nft_set_series_sale_window(
+        &mut self,
         token_series_id: TokenSeriesId,
-        from_index: Option<U128>,
-        limit: Option<u64>,
-    ) -> Vec<Token> {
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
-        let tokens = self
+        sale_start: Option<TimestampSec>,
+        sale_end: Option<TimestampSec>,
+    ) {
+        assert_one_yocto();
+
+        let mut token_series = self
             .token_series_by_id
             .get(&token_series_id)
-            .unwrap()
-            .tokens;
-        assert!(
-            (tokens.len() as u128) > start_index,
-            "Out of bounds, please use a smaller from_index."
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
         );
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
 
-        tokens
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|token_id| self.nft_token(token_id).unwrap())
-            .collect()
-    }
+        assert_valid_sale_window(sale_start, sale_end);
 
-    pub fn nft_token(&self, token_id: TokenId) -> Option<Token> {
-        let owner_id = self.tokens.owner_by_id.get(&token_id)?;
-        let approved_account_ids = self
-            .tokens
-            .approvals_by_id
-            .as_ref()
-            .and_then(|by_id| by_id.get(&token_id).or_else(|| Some(HashMap::new())));
+        token_series.sale_start = sale_start;
+        token_series.sale_end = sale_end;
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
 
-        // CUSTOM (switch metadata for the token_series metadata)
-        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
-        let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
-        let series_metadata = self
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetSaleWindow(vec![
+            SeriesSetSaleWindowData {
+                token_series_id,
+                sale_start,
+                sale_end,
+            },
+        ]));
+    }
+
+    #[payable]
+    pub fn nft_set_series_max_per_account(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        max_per_account: Option<u32>,
+    ) {
+        assert_one_yocto();
+
+        let mut token_series = self
             .token_series_by_id
             .get(&token_series_id)
-            .unwrap()
-            .metadata;
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
 
-        let mut token_metadata = self
-            .tokens
-            .token_metadata_by_id
-            .as_ref()
-            .unwrap()
-            .get(&token_id)
-            .unwrap();
+        token_series.max_per_account = max_per_account;
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
 
-        token_metadata.title = Some(format!(
-            "{}{}{}",
-            series_metadata.title.unwrap(),
-            TITLE_DELIMETER,
-            token_id_iter.next().unwrap()
-        ));
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetMaxPerAccount(vec![
+            SeriesSetMaxPerAccountData {
+                token_series_id,
+                max_per_account,
+            },
+        ]));
+    }
 
-        token_metadata.reference = series_metadata.reference;
-        token_metadata.media = series_metadata.media;
-        token_metadata.copies = series_metadata.copies;
-        token_metadata.extra = series_metadata.extra;
+    #[payable]
+    pub fn nft_set_series_price_strategy(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        price_strategy: Option<PriceStrategy>,
+    ) {
+        assert_one_yocto();
 
-        Some(Token {
-            token_id,
-            owner_id,
-            metadata: Some(token_metadata),
-            approved_account_ids,
-        })
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+
+        assert!(
+            token_series.is_mintable,
+            "Nearlend Dao token series is not mintable"
+        );
+
+        if let Some(price_strategy) = &price_strategy {
+            price_strategy.assert_valid();
+        }
+
+        token_series.price_strategy = price_strategy.clone();
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        // set market data transaction fee
+        let current_transaction_fee = self.calculate_current_transaction_fee();
+        self.market_data_transaction_fee
+            .transaction_fee
+            .insert(&token_series_id, &current_transaction_fee);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetPriceStrategy(vec![
+            SeriesSetPriceStrategyData {
+                token_series_id,
+                price_strategy,
+                transaction_fee: Some(current_transaction_fee.to_string()),
+            },
+        ]));
     }
 
-    // CUSTOM core standard repeated here because no macro below
+    #[payable]
+    pub fn nft_set_series_presale(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        public_sale_start: Option<TimestampSec>,
+    ) {
+        assert_one_yocto();
 
-    pub fn nft_transfer_unsafe(
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
+        );
+
+        token_series.public_sale_start = public_sale_start;
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesSetPresale(vec![
+            SeriesSetPresaleData {
+                token_series_id,
+                public_sale_start,
+            },
+        ]));
+    }
+
+    #[payable]
+    pub fn nft_add_series_presale_allowlist(
         &mut self,
-        receiver_id: ValidAccountId,
-        token_id: TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
+        token_series_id: TokenSeriesId,
+        allowlist: Vec<PresaleAllowlistEntry>,
     ) {
-        let sender_id = env::predecessor_account_id();
-        let receiver_id_str = receiver_id.to_string();
-        let (previous_owner_id, _) = self.tokens.internal_transfer(
-            &sender_id,
-            &receiver_id_str,
-            &token_id,
-            approval_id,
-            memo.clone(),
+        let initial_storage_usage = env::storage_usage();
+
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
         );
 
-        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
-            Some(sender_id)
-        } else {
-            None
-        };
+        for entry in allowlist.iter() {
+            if let Some(price) = entry.price {
+                assert!(
+                    price.0 < MAX_PRICE,
+                    "Nearlend Dao price higher than {}",
+                    MAX_PRICE
+                );
+            }
+            let key = series_account_key(&token_series_id, entry.account_id.as_ref());
+            // keep what was already bought when an account is re-added
+            let minted = self
+                .presale_allowlist
+                .get(&key)
+                .map(|allowance| allowance.minted)
+                .unwrap_or(0);
+            self.presale_allowlist.insert(
+                &key,
+                &PresaleAllowance {
+                    quota: entry.quota,
+                    minted,
+                    price: entry.price.map(|price| price.0),
+                },
+            );
+        }
 
-        NearEvent::log_nft_transfer(
-            previous_owner_id,
-            receiver_id_str,
-            vec![token_id],
-            memo,
-            authorized_id,
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesAddPresaleAllowlist(vec![
+            SeriesAddPresaleAllowlistData {
+                token_series_id,
+                allowlist: allowlist
+                    .into_iter()
+                    .map(|entry| PresaleAllowlistData {
+                        account_id: entry.account_id.to_string(),
+                        quota: entry.quota,
+                        price: entry.price.map(|price| price.0.to_string()),
+                    })
+                    .collect(),
+            },
+        ]));
+
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
+    }
+
+    #[payable]
+    pub fn nft_remove_series_presale_allowlist(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        account_ids: Vec<ValidAccountId>,
+    ) {
+        assert_one_yocto();
+
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Nearlend Dao Creator only"
         );
+
+        for account_id in account_ids.iter() {
+            self.presale_allowlist
+                .remove(&series_account_key(&token_series_id, account_id.as_ref()));
+        }
+
+        NearEvent::log_nearlend_series(NearlendSeriesEventKind::SeriesRemovePresaleAllowlist(
+            vec![SeriesRemovePresaleAllowlistData {
+                token_series_id,
+                account_ids: account_ids.iter().map(|id| id.to_string()).collect(),
+            }],
+        ));
     }
 
+    /// Owner or an approved account locks the token to `locker_id`, e.g. a lending contract
     #[payable]
-    pub fn nft_transfer(
+    pub fn nft_lock(
         &mut self,
-        receiver_id: ValidAccountId,
         token_id: TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
-    ) {
+        locker_id: ValidAccountId,
+        reason: Option<String>,
+    ) -> TokenLock {
+        assert!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
         let sender_id = env::predecessor_account_id();
-        let previous_owner_id = self
-            .tokens
-            .owner_by_id
-            .get(&token_id)
-            .expect("Token not found");
-        let receiver_id_str = receiver_id.to_string();
-        self.tokens
-            .nft_transfer(receiver_id, token_id.clone(), approval_id, memo.clone());
 
-        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
+        let owner_id = self.internal_assert_owner_or_approved(&token_id, &sender_id);
+        assert!(
+            self.token_locks.get(&token_id).is_none(),
+            "Nearlend Dao token is already locked"
+        );
+
+        let token_lock = TokenLock {
+            locker_id: locker_id.to_string(),
+            reason: reason.clone(),
+            locked_at: to_sec(env::block_timestamp()),
+        };
+        self.token_locks.insert(&token_id, &token_lock);
+
+        let authorized_id: Option<AccountId> = if sender_id != owner_id {
             Some(sender_id)
         } else {
             None
         };
-
-        NearEvent::log_nft_transfer(
-            previous_owner_id,
-            receiver_id_str,
+        NearEvent::log_token_lock(
+            owner_id,
+            locker_id.to_string(),
             vec![token_id],
-            memo,
+            reason,
             authorized_id,
         );
+
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
+        token_lock
     }
 
     #[payable]
-    pub fn nft_transfer_call(
+    pub fn nft_unlock(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+
+        let token_lock = self
+            .token_locks
+            .get(&token_id)
+            .expect("Nearlend Dao token is not locked");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_lock.locker_id,
+            "Nearlend Dao locker only"
+        );
+        self.token_locks.remove(&token_id);
+
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(&token_id)
+            .expect("Token not found");
+        NearEvent::log_token_unlock(owner_id, token_lock.locker_id, vec![token_id]);
+    }
+
+    /// Owner or an approved account lends the utility of the token to `user_id` until `expires`
+    #[payable]
+    pub fn nft_set_user(
         &mut self,
-        receiver_id: ValidAccountId,
         token_id: TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
-        msg: String,
-    ) -> PromiseOrValue<bool> {
-        assert_one_yocto();
+        user_id: ValidAccountId,
+        expires: TimestampSec,
+    ) -> TokenUser {
+        assert!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
         let sender_id = env::predecessor_account_id();
-        let (previous_owner_id, old_approvals) = self.tokens.internal_transfer(
-            &sender_id,
-            receiver_id.as_ref(),
-            &token_id,
-            approval_id,
-            memo.clone(),
+
+        let owner_id = self.internal_assert_owner_or_approved(&token_id, &sender_id);
+        assert!(
+            expires > to_sec(env::block_timestamp()),
+            "Nearlend Dao user expires must be in the future"
         );
 
-        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
-            Some(sender_id.clone())
+        let token_user = TokenUser {
+            user_id: user_id.to_string(),
+            expires,
+        };
+        self.token_users.insert(&token_id, &token_user);
+
+        let authorized_id: Option<AccountId> = if sender_id != owner_id {
+            Some(sender_id)
         } else {
             None
         };
-
-        NearEvent::log_nft_transfer(
-            previous_owner_id.clone(),
-            receiver_id.to_string(),
-            vec![token_id.clone()],
-            memo,
+        NearEvent::log_token_update_user(
+            owner_id,
+            Some(user_id.to_string()),
+            Some(expires),
+            vec![token_id],
             authorized_id,
         );
 
-        // Initiating receiver's call and the callback
-        ext_non_fungible_token_receiver::nft_on_transfer(
-            sender_id,
-            previous_owner_id.clone(),
-            token_id.clone(),
-            msg,
-            receiver_id.as_ref(),
-            NO_DEPOSIT,
-            env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL,
-        )
-        .then(ext_self::nft_resolve_transfer(
-            previous_owner_id,
-            receiver_id.into(),
-            token_id,
-            old_approvals,
-            &env::current_account_id(),
-            NO_DEPOSIT,
-            GAS_FOR_RESOLVE_TRANSFER,
-        ))
-        .into()
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
+        token_user
     }
 
-    // CUSTOM enumeration standard modified here because no macro below
-
-    pub fn nft_total_supply(&self) -> U128 {
-        (self.tokens.owner_by_id.len() as u128).into()
-    }
-
-    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
-        // Get starting index, whether or not it was explicitly given.
-        // Defaults to 0 based on the spec:
-        // https://nomicon.io/Standards/NonFungibleToken/Enumeration.html#interface
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+    #[payable]
+    pub fn nft_remove_user(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_assert_owner_or_approved(&token_id, &sender_id);
         assert!(
-            (self.tokens.owner_by_id.len() as u128) > start_index,
-            "Out of bounds, please use a smaller from_index."
-        );
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
-        self.tokens
-            .owner_by_id
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|(token_id, _)| self.nft_token(token_id).unwrap())
-            .collect()
-    }
-
-    pub fn nft_supply_for_owner(self, account_id: ValidAccountId) -> U128 {
-        let tokens_per_owner = self.tokens.tokens_per_owner.expect(
-            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
+            self.token_users.contains_key(&token_id),
+            "Nearlend Dao token has no user"
         );
-        tokens_per_owner
-            .get(account_id.as_ref())
-            .map(|account_tokens| U128::from(account_tokens.len() as u128))
-            .unwrap_or(U128(0))
+        self.internal_clear_token_user(&token_id);
     }
 
-    pub fn nft_tokens_for_owner(
+    fn internal_assert_owner_or_approved(
         &self,
-        account_id: ValidAccountId,
-        from_index: Option<U128>,
-        limit: Option<u64>,
-    ) -> Vec<Token> {
-        let tokens_per_owner = self.tokens.tokens_per_owner.as_ref().expect(
-            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
-        );
-        let token_set = if let Some(token_set) = tokens_per_owner.get(account_id.as_ref()) {
-            token_set
-        } else {
-            return vec![];
-        };
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
-        assert!(
-            token_set.len() as u128 > start_index,
-            "Out of bounds, please use a smaller from_index."
-        );
-        token_set
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|token_id| self.nft_token(token_id).unwrap())
-            .collect()
+        token_id: &TokenId,
+        sender_id: &AccountId,
+    ) -> AccountId {
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(token_id)
+            .expect("Token not found");
+        if sender_id != &owner_id {
+            let is_approved = self
+                .tokens
+                .approvals_by_id
+                .as_ref()
+                .and_then(|by_id| by_id.get(token_id))
+                .is_some_and(|approvals| approvals.contains_key(sender_id));
+            assert!(is_approved, "Nearlend Dao owner or approved account only");
+        }
+        owner_id
     }
 
-    pub fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: u32) -> Payout {
-        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("No token id");
-        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
-        let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
-        let royalty = self
-            .token_series_by_id
-            .get(&token_series_id)
-            .expect("no type")
-            .royalty;
-
-        assert!(
-            royalty.len() as u32 <= max_len_payout,
-            "Market cannot payout to that many receivers"
-        );
+    /// Removes the rental user, called whenever the token changes hands
+    fn internal_clear_token_user(&mut self, token_id: &TokenId) {
+        if self.token_users.remove(token_id).is_none() {
+            return;
+        }
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(token_id)
+            .expect("Token not found");
+        NearEvent::log_token_update_user(owner_id, None, None, vec![token_id.clone()], None);
+    }
 
-        let balance_u128: u128 = balance.into();
+    /// Rental user that has not expired yet
+    fn internal_token_user(&self, token_id: &TokenId) -> Option<TokenUser> {
+        self.token_users
+            .get(token_id)
+            .filter(|token_user| token_user.expires > to_sec(env::block_timestamp()))
+    }
 
-        let mut payout: Payout = Payout {
-            payout: HashMap::new(),
+    /// Account a transfer is performed as. A locked token can only be moved by its locker, which
+    /// acts on behalf of the owner and releases the lock.
+    fn internal_transfer_sender(&mut self, token_id: &TokenId) -> AccountId {
+        self.assert_not_paused(PauseOperation::Transfer);
+        let sender_id = env::predecessor_account_id();
+        let token_lock = match self.token_locks.get(token_id) {
+            Some(token_lock) => token_lock,
+            None => return sender_id,
         };
-        let mut total_perpetual = 0;
+        assert_eq!(
+            sender_id, token_lock.locker_id,
+            "Nearlend Dao token is locked by {}",
+            token_lock.locker_id
+        );
+        self.token_locks.remove(token_id);
 
-        for (k, v) in royalty.iter() {
-            if *k != owner_id {
-                let key = k.clone();
-                payout
-                    .payout
-                    .insert(key, royalty_to_payout(*v, balance_u128));
-                total_perpetual += *v;
-            }
-        }
-        payout.payout.insert(
-            owner_id,
-            royalty_to_payout(10000 - total_perpetual, balance_u128),
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(token_id)
+            .expect("Token not found");
+        NearEvent::log_token_unlock(
+            owner_id.clone(),
+            token_lock.locker_id,
+            vec![token_id.clone()],
         );
-        payout
+        owner_id
     }
 
+    /// Burns the token and refunds the freed storage to the caller, credited to its storage balance
+    /// when registered. With `remove_from_series` the token is also removed from the series tokens.
     #[payable]
-    pub fn nft_transfer_payout(
-        &mut self,
-        receiver_id: ValidAccountId,
-        token_id: TokenId,
-        approval_id: Option<u64>,
-        balance: Option<U128>,
-        max_len_payout: Option<u32>,
-    ) -> Option<Payout> {
+    pub fn nft_burn(&mut self, token_id: TokenId, remove_from_series: Option<bool>) {
         assert_one_yocto();
+        self.assert_not_paused(PauseOperation::Burn);
+        let initial_storage_usage = env::storage_usage();
 
-        let sender_id = env::predecessor_account_id();
-        // Transfer
-        let previous_token = self.nft_token(token_id.clone()).expect("no token");
-        self.tokens
-            .nft_transfer(receiver_id.clone(), token_id.clone(), approval_id, None);
-
-        // Payout calculation
-        let previous_owner_id = previous_token.owner_id;
-        let mut total_perpetual = 0;
-        let payout = if let Some(balance) = balance {
-            let balance_u128: u128 = u128::from(balance);
-            let mut payout: Payout = Payout {
-                payout: HashMap::new(),
-            };
-
-            let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
-            let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
-            let royalty = self
-                .token_series_by_id
-                .get(&token_series_id)
-                .expect("no type")
-                .royalty;
-
-            assert!(
-                royalty.len() as u32 <= max_len_payout.unwrap(),
-                "Market cannot payout to that many receivers"
+        let owner_id = self.tokens.owner_by_id.get(&token_id).unwrap();
+        if let Some(token_lock) = self.token_locks.get(&token_id) {
+            assert_eq!(
+                token_lock.locker_id,
+                env::predecessor_account_id(),
+                "Nearlend Dao token is locked by {}",
+                token_lock.locker_id
             );
-            for (k, v) in royalty.iter() {
-                let key = k.clone();
-                if key != previous_owner_id {
-                    payout
-                        .payout
-                        .insert(key, royalty_to_payout(*v, balance_u128));
-                    total_perpetual += *v;
-                }
-            }
-
-            assert!(total_perpetual <= 10000, "Total payout overflow");
-
-            payout.payout.insert(
-                previous_owner_id.clone(),
-                royalty_to_payout(10000 - total_perpetual, balance_u128),
+            self.token_locks.remove(&token_id);
+            NearEvent::log_token_unlock(
+                owner_id.clone(),
+                token_lock.locker_id,
+                vec![token_id.clone()],
             );
-            Some(payout)
         } else {
-            None
-        };
+            assert_eq!(owner_id, env::predecessor_account_id(), "Token owner only");
+        }
+        self.internal_clear_token_user(&token_id);
 
-        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
-            Some(sender_id)
-        } else {
-            None
-        };
+        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
+            next_approval_id_by_id.remove(&token_id);
+        }
 
-        NearEvent::log_nft_transfer(
-            previous_owner_id,
-            receiver_id.to_string(),
-            vec![token_id],
-            None,
-            authorized_id,
-        );
+        if let Some(approvals_by_id) = &mut self.tokens.approvals_by_id {
+            approvals_by_id.remove(&token_id);
+        }
 
-        payout
-    }
+        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
+            let mut token_ids = tokens_per_owner.get(&owner_id).unwrap();
+            token_ids.remove(&token_id);
+            tokens_per_owner.insert(&owner_id, &token_ids);
+        }
 
-    pub fn get_owner(&self) -> AccountId {
-        self.tokens.owner_id.clone()
-    }
-}
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            token_metadata_by_id.remove(&token_id);
+        }
 
-fn royalty_to_payout(a: u32, b: Balance) -> U128 {
-    U128(a as u128 * b / 10_000u128)
-}
+        self.tokens.owner_by_id.remove(&token_id);
+        let storage_payer_id = self.storage_payers.remove(&token_id);
 
-// near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
-// near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
-near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
+        if remove_from_series.unwrap_or(false) {
+            let token_series_id: TokenSeriesId =
+                token_id.split(TOKEN_DELIMETER).next().unwrap().to_string();
+            let mut token_series = self
+                .token_series_by_id
+                .get(&token_series_id)
+                .expect("Nearlend Dao Token series not exist");
+            if token_series.tokens.remove(&token_id) {
+                token_series.burned += 1;
+                self.token_series_by_id
+                    .insert(&token_series_id, &token_series);
+            }
+        }
 
-#[near_bindgen]
-impl NonFungibleTokenMetadataProvider for Contract {
-    fn nft_metadata(&self) -> NFTContractMetadata {
-        self.metadata.get().unwrap()
+        // tokens minted before payers were recorded keep their storage in the contract
+        if let Some(storage_payer_id) = storage_payer_id {
+            self.internal_refund_storage(
+                &storage_payer_id,
+                initial_storage_usage.saturating_sub(env::storage_usage()),
+            );
+        }
+
+        NearEvent::log_nft_burn(owner_id, vec![token_id], None, None);
     }
-}
 
-#[near_bindgen]
-impl NonFungibleTokenResolver for Contract {
+    // Fungible token payouts
+
+    /// Keeps a failed payout so the receiver can withdraw it after registering with the token
     #[private]
-    fn nft_resolve_transfer(
+    pub fn ft_resolve_payout(
         &mut self,
-        previous_owner_id: AccountId,
+        ft_token_id: AccountId,
         receiver_id: AccountId,
-        token_id: TokenId,
-        approved_account_ids: Option<HashMap<AccountId, u64>>,
-    ) -> bool {
-        let resp: bool = self.tokens.nft_resolve_transfer(
-            previous_owner_id.clone(),
-            receiver_id.clone(),
-            token_id.clone(),
-            approved_account_ids,
-        );
-
-        // if not successful, return nft back to original owner
-        if !resp {
-            NearEvent::log_nft_transfer(receiver_id, previous_owner_id, vec![token_id], None, None);
+        amount: U128,
+    ) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return;
         }
-
-        resp
+        let key = ft_account_key(&ft_token_id, &receiver_id);
+        let owed = self.ft_payouts.get(&key).unwrap_or(0);
+        self.ft_payouts.insert(&key, &(owed + amount.0));
     }
-}
 
-/// from https://github.com/near/near-sdk-rs/blob/e4abb739ff953b06d718037aa1b8ab768db17348/near-contract-standards/src/non_fungible_token/utils.rs#L29
+    /// Sends the caller the payouts in `ft_token_id` that failed, kept again if it fails once more
+    #[payable]
+    pub fn ft_withdraw_payout(&mut self, ft_token_id: ValidAccountId) -> Promise {
+        assert_one_yocto();
+        let receiver_id = env::predecessor_account_id();
+        let amount = self
+            .ft_payouts
+            .remove(&ft_account_key(ft_token_id.as_ref(), &receiver_id))
+            .expect("Nearlend Dao no payout to withdraw");
+        internal_ft_payout(ft_token_id.as_ref(), receiver_id, amount)
+    }
 
-fn refund_deposit(storage_used: u64, extra_spend: Balance) {
-    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
-    let attached_deposit = env::attached_deposit() - extra_spend;
+    pub fn ft_payout_of(&self, ft_token_id: ValidAccountId, account_id: ValidAccountId) -> U128 {
+        self.ft_payouts
+            .get(&ft_account_key(ft_token_id.as_ref(), account_id.as_ref()))
+            .unwrap_or(0)
+            .into()
+    }
 
-    assert!(
-        required_cost <= attached_deposit,
-        "Must attach {} yoctoNEAR to cover storage",
-        required_cost,
-    );
+    // CUSTOM VIEWS
 
-    let refund = attached_deposit - required_cost;
-    if refund > 1 {
-        Promise::new(env::predecessor_account_id()).transfer(refund);
+    pub fn nft_get_series_single(&self, token_series_id: TokenSeriesId) -> TokenSeriesJson {
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Series does not exist");
+        let current_transaction_fee = self.get_market_data_transaction_fee(&token_series_id);
+        token_series.into_json(token_series_id, Some(current_transaction_fee.into()))
     }
-}
 
-fn to_sec(timestamp: Timestamp) -> TimestampSec {
-    (timestamp / 10u64.pow(9)) as u32
-}
+    pub fn nft_get_series_minted_by_account(
+        &self,
+        token_series_id: TokenSeriesId,
+        account_id: ValidAccountId,
+    ) -> u32 {
+        self.minted_per_account
+            .get(&series_account_key(&token_series_id, account_id.as_ref()))
+            .unwrap_or(0)
+    }
 
-#[cfg(all(test, not(target_arch = "wasm32")))]
+    pub fn nft_get_series_presale_allowance(
+        &self,
+        token_series_id: TokenSeriesId,
+        account_id: ValidAccountId,
+    ) -> Option<PresaleAllowanceJson> {
+        self.presale_allowlist
+            .get(&series_account_key(&token_series_id, account_id.as_ref()))
+            .map(|allowance| PresaleAllowanceJson {
+                account_id: account_id.into(),
+                quota: allowance.quota,
+                minted: allowance.minted,
+                price: allowance.price.map(U128::from),
+            })
+    }
+
+    pub fn nft_get_auction(&self, token_series_id: TokenSeriesId) -> Option<AuctionJson> {
+        self.auctions
+            .get(&token_series_id)
+            .map(|auction| auction.to_json(token_series_id))
+    }
+
+    pub fn nft_get_auctions(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AuctionJson> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+
+        self.auctions
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(token_series_id, auction)| auction.to_json(token_series_id))
+            .collect()
+    }
+
+    pub fn nft_token_lock(&self, token_id: TokenId) -> Option<TokenLock> {
+        self.token_locks.get(&token_id)
+    }
+
+    pub fn nft_token_user(&self, token_id: TokenId) -> Option<TokenUser> {
+        self.internal_token_user(&token_id)
+    }
+
+    pub fn nft_is_locked(&self, token_id: TokenId) -> bool {
+        self.token_locks.contains_key(&token_id)
+    }
+
+    pub fn nft_get_series_format(self) -> (char, &'static str, &'static str) {
+        (TOKEN_DELIMETER, TITLE_DELIMETER, EDITION_DELIMETER)
+    }
+
+    pub fn nft_get_series_price(self, token_series_id: TokenSeriesId) -> Option<U128> {
+        let price = self.token_series_by_id.get(&token_series_id).unwrap().price;
+        match price {
+            Some(p) => return Some(U128::from(p)),
+            None => return None,
+        };
+    }
+
+    pub fn nft_get_series_current_price(&self, token_series_id: TokenSeriesId) -> Option<U128> {
+        self.token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist")
+            .current_price(to_sec(env::block_timestamp()))
+            .map(U128::from)
+    }
+
+    /// Prices of the next `count` editions, bounded by the remaining copies and MAX_PRICE_QUOTE
+    pub fn nft_get_series_price_quote(
+        &self,
+        token_series_id: TokenSeriesId,
+        count: u64,
+    ) -> Vec<U128> {
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist");
+        let minted = token_series.minted();
+        let remaining = token_series
+            .metadata
+            .copies
+            .map_or(count, |copies| copies.saturating_sub(minted));
+        let count = count.min(remaining).min(MAX_PRICE_QUOTE);
+        let now = to_sec(env::block_timestamp());
+
+        match &token_series.price_strategy {
+            Some(price_strategy) => price_strategy
+                .quote(now, minted, count)
+                .into_iter()
+                .map(U128::from)
+                .collect(),
+            None => match token_series.price {
+                Some(price) => vec![U128::from(price); count as usize],
+                None => vec![],
+            },
+        }
+    }
+
+    pub fn nft_get_series(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<TokenSeriesJson> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            (self.token_series_by_id.len() as u128) > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+
+        self.token_series_by_id
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(token_series_id, token_series)| token_series.into_json(token_series_id, None))
+            .collect()
+    }
+
+    pub fn nft_supply_for_series(&self, token_series_id: TokenSeriesId) -> U64 {
+        self.token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series not exist")
+            .tokens
+            .len()
+            .into()
+    }
+
+    pub fn nft_tokens_by_series(
+        &self,
+        token_series_id: TokenSeriesId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<TokenJson> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let tokens = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .unwrap()
+            .tokens;
+        assert!(
+            (tokens.len() as u128) > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+
+        tokens
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|token_id| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    pub fn nft_token(&self, token_id: TokenId) -> Option<TokenJson> {
+        let owner_id = self.tokens.owner_by_id.get(&token_id)?;
+        let approved_account_ids = self
+            .tokens
+            .approvals_by_id
+            .as_ref()
+            .and_then(|by_id| by_id.get(&token_id).or_else(|| Some(HashMap::new())));
+
+        // CUSTOM (switch metadata for the token_series metadata)
+        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
+        let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
+        let token_series = self.token_series_by_id.get(&token_series_id).unwrap();
+        let series_metadata = token_series.metadata;
+
+        let mut token_metadata = self
+            .tokens
+            .token_metadata_by_id
+            .as_ref()
+            .unwrap()
+            .get(&token_id)
+            .unwrap();
+
+        let edition = token_id_iter.next().unwrap();
+        token_metadata.title = Some(format!(
+            "{}{}{}",
+            series_metadata.title.unwrap(),
+            TITLE_DELIMETER,
+            edition
+        ));
+
+        let revealed_reference = token_series
+            .reveal
+            .and_then(|reveal| reveal.revealed)
+            .and_then(|revealed| revealed.reference(edition.parse().unwrap()));
+        if let Some(revealed_reference) = revealed_reference {
+            token_metadata.reference = Some(revealed_reference);
+            token_metadata.reference_hash = None;
+        }
+
+        // edition fields take precedence over the series ones
+        if token_metadata.media.is_none() {
+            token_metadata.media = series_metadata.media;
+            token_metadata.media_hash = series_metadata.media_hash;
+        }
+        if token_metadata.reference.is_none() {
+            token_metadata.reference = series_metadata.reference;
+            token_metadata.reference_hash = series_metadata.reference_hash;
+        }
+        token_metadata.copies = series_metadata.copies;
+        token_metadata.extra = merge_extra(series_metadata.extra, token_metadata.extra);
+
+        let token_user = self.internal_token_user(&token_id);
+
+        Some(TokenJson {
+            token_id,
+            owner_id,
+            metadata: Some(token_metadata),
+            approved_account_ids,
+            user_id: token_user
+                .as_ref()
+                .map(|token_user| token_user.user_id.clone()),
+            user_expires: token_user.map(|token_user| token_user.expires),
+        })
+    }
+
+    // CUSTOM core standard repeated here because no macro below
+
+    pub fn nft_transfer_unsafe(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        let sender_id = env::predecessor_account_id();
+        let transfer_sender_id = self.internal_transfer_sender(&token_id);
+        self.internal_clear_token_user(&token_id);
+        let receiver_id_str = receiver_id.to_string();
+        let (previous_owner_id, _) = self.tokens.internal_transfer(
+            &transfer_sender_id,
+            &receiver_id_str,
+            &token_id,
+            approval_id,
+            memo.clone(),
+        );
+
+        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
+            Some(sender_id)
+        } else {
+            None
+        };
+
+        NearEvent::log_nft_transfer(
+            previous_owner_id,
+            receiver_id_str,
+            vec![token_id],
+            memo,
+            authorized_id,
+        );
+    }
+
+    #[payable]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        let transfer_sender_id = self.internal_transfer_sender(&token_id);
+        self.internal_clear_token_user(&token_id);
+        let receiver_id_str = receiver_id.to_string();
+        let (previous_owner_id, _) = self.tokens.internal_transfer(
+            &transfer_sender_id,
+            &receiver_id_str,
+            &token_id,
+            approval_id,
+            memo.clone(),
+        );
+
+        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
+            Some(sender_id)
+        } else {
+            None
+        };
+
+        NearEvent::log_nft_transfer(
+            previous_owner_id,
+            receiver_id_str,
+            vec![token_id],
+            memo,
+            authorized_id,
+        );
+    }
+
+    #[payable]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        let transfer_sender_id = self.internal_transfer_sender(&token_id);
+        self.internal_clear_token_user(&token_id);
+        let (previous_owner_id, old_approvals) = self.tokens.internal_transfer(
+            &transfer_sender_id,
+            receiver_id.as_ref(),
+            &token_id,
+            approval_id,
+            memo.clone(),
+        );
+
+        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
+            Some(sender_id.clone())
+        } else {
+            None
+        };
+
+        NearEvent::log_nft_transfer(
+            previous_owner_id.clone(),
+            receiver_id.to_string(),
+            vec![token_id.clone()],
+            memo,
+            authorized_id,
+        );
+
+        // Initiating receiver's call and the callback
+        ext_non_fungible_token_receiver::nft_on_transfer(
+            sender_id,
+            previous_owner_id.clone(),
+            token_id.clone(),
+            msg,
+            receiver_id.as_ref(),
+            NO_DEPOSIT,
+            env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL,
+        )
+        .then(ext_self::nft_resolve_transfer(
+            previous_owner_id,
+            receiver_id.into(),
+            token_id,
+            old_approvals,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_TRANSFER,
+        ))
+        .into()
+    }
+
+    // CUSTOM enumeration standard modified here because no macro below
+
+    pub fn nft_total_supply(&self) -> U128 {
+        (self.tokens.owner_by_id.len() as u128).into()
+    }
+
+    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<TokenJson> {
+        // Get starting index, whether or not it was explicitly given.
+        // Defaults to 0 based on the spec:
+        // https://nomicon.io/Standards/NonFungibleToken/Enumeration.html#interface
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            (self.tokens.owner_by_id.len() as u128) > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        self.tokens
+            .owner_by_id
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(token_id, _)| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(self, account_id: ValidAccountId) -> U128 {
+        let tokens_per_owner = self.tokens.tokens_per_owner.expect(
+            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
+        );
+        tokens_per_owner
+            .get(account_id.as_ref())
+            .map(|account_tokens| U128::from(account_tokens.len() as u128))
+            .unwrap_or(U128(0))
+    }
+
+    pub fn nft_tokens_for_owner(
+        &self,
+        account_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<TokenJson> {
+        let tokens_per_owner = self.tokens.tokens_per_owner.as_ref().expect(
+            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
+        );
+        let token_set = if let Some(token_set) = tokens_per_owner.get(account_id.as_ref()) {
+            token_set
+        } else {
+            return vec![];
+        };
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            token_set.len() as u128 > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        token_set
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|token_id| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    pub fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: u32) -> Payout {
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("No token id");
+        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
+        let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
+        let royalty = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("no type")
+            .royalty;
+
+        assert!(
+            royalty.len() as u32 <= max_len_payout,
+            "Market cannot payout to that many receivers"
+        );
+
+        let balance_u128: u128 = balance.into();
+
+        let mut payout: Payout = Payout {
+            payout: HashMap::new(),
+        };
+        let mut total_perpetual = 0;
+
+        for (k, v) in royalty.iter() {
+            if *k != owner_id {
+                let key = k.clone();
+                payout
+                    .payout
+                    .insert(key, royalty_to_payout(*v, balance_u128));
+                total_perpetual += *v;
+            }
+        }
+        payout.payout.insert(
+            owner_id,
+            royalty_to_payout(10000 - total_perpetual, balance_u128),
+        );
+        payout
+    }
+
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        balance: Option<U128>,
+        max_len_payout: Option<u32>,
+    ) -> Option<Payout> {
+        assert_one_yocto();
+
+        let sender_id = env::predecessor_account_id();
+        // Transfer
+        let previous_token = self.nft_token(token_id.clone()).expect("no token");
+        let transfer_sender_id = self.internal_transfer_sender(&token_id);
+        self.internal_clear_token_user(&token_id);
+        self.tokens.internal_transfer(
+            &transfer_sender_id,
+            receiver_id.as_ref(),
+            &token_id,
+            approval_id,
+            None,
+        );
+
+        // Payout calculation
+        let previous_owner_id = previous_token.owner_id;
+        let mut total_perpetual = 0;
+        let payout = if let Some(balance) = balance {
+            let balance_u128: u128 = u128::from(balance);
+            let mut payout: Payout = Payout {
+                payout: HashMap::new(),
+            };
+
+            let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
+            let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
+            let royalty = self
+                .token_series_by_id
+                .get(&token_series_id)
+                .expect("no type")
+                .royalty;
+
+            assert!(
+                royalty.len() as u32 <= max_len_payout.unwrap(),
+                "Market cannot payout to that many receivers"
+            );
+            for (k, v) in royalty.iter() {
+                let key = k.clone();
+                if key != previous_owner_id {
+                    payout
+                        .payout
+                        .insert(key, royalty_to_payout(*v, balance_u128));
+                    total_perpetual += *v;
+                }
+            }
+
+            assert!(total_perpetual <= 10000, "Total payout overflow");
+
+            payout.payout.insert(
+                previous_owner_id.clone(),
+                royalty_to_payout(10000 - total_perpetual, balance_u128),
+            );
+            Some(payout)
+        } else {
+            None
+        };
+
+        let authorized_id: Option<AccountId> = if sender_id != previous_owner_id {
+            Some(sender_id)
+        } else {
+            None
+        };
+
+        NearEvent::log_nft_transfer(
+            previous_owner_id,
+            receiver_id.to_string(),
+            vec![token_id],
+            None,
+            authorized_id,
+        );
+
+        payout
+    }
+
+    pub fn get_owner(&self) -> OwnerJson {
+        OwnerJson {
+            owner_id: self.tokens.owner_id.clone(),
+            pending_owner: self.pending_owner.clone(),
+        }
+    }
+
+    // Storage
+
+    /// Pays for `storage_used` with the attached deposit minus `extra_spend` and refunds the rest.
+    /// When the deposit is short, the difference is drawn from the caller's storage balance.
+    fn internal_charge_storage(&mut self, storage_used: StorageUsage, extra_spend: Balance) {
+        let account_id = env::predecessor_account_id();
+        let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+        let attached_deposit = env::attached_deposit() - extra_spend;
+
+        if required_cost <= attached_deposit {
+            let refund = attached_deposit - required_cost;
+            if refund > 1 {
+                Promise::new(account_id).transfer(refund);
+            }
+            return;
+        }
+
+        let shortfall = required_cost - attached_deposit;
+        assert!(
+            shortfall <= self.internal_available_storage_balance(&account_id),
+            "Must attach {} yoctoNEAR to cover storage or add it with storage_deposit",
+            shortfall,
+        );
+        self.internal_spend_storage_balance(&account_id, shortfall);
+    }
+
+    /// Pays for `storage_used` with the storage balance of `account_id` only, for calls where
+    /// the payer is not the predecessor
+    fn internal_charge_storage_balance(
+        &mut self,
+        account_id: &AccountId,
+        storage_used: StorageUsage,
+    ) {
+        let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+        assert!(
+            required_cost <= self.internal_available_storage_balance(account_id),
+            "Nearlend Dao {} must add {} yoctoNEAR with storage_deposit to cover storage",
+            account_id,
+            required_cost,
+        );
+        self.internal_spend_storage_balance(account_id, required_cost);
+    }
+
+    fn internal_available_storage_balance(&self, account_id: &AccountId) -> Balance {
+        self.storage_deposits
+            .get(account_id)
+            .unwrap_or(0)
+            .saturating_sub(self.storage_balance_bounds().min.0)
+    }
+
+    fn internal_spend_storage_balance(&mut self, account_id: &AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+        let balance = self.storage_deposits.get(account_id).unwrap_or(0);
+        self.storage_deposits
+            .insert(account_id, &(balance - amount));
+    }
+
+    /// Gives back the cost of `storage_freed`, credited to the storage balance when registered
+    fn internal_refund_storage(&mut self, account_id: &AccountId, storage_freed: StorageUsage) {
+        let refund = env::storage_byte_cost() * Balance::from(storage_freed);
+        if refund == 0 {
+            return;
+        }
+        match self.storage_deposits.get(account_id) {
+            Some(balance) => {
+                self.storage_deposits
+                    .insert(account_id, &(balance + refund));
+            }
+            None => {
+                Promise::new(account_id.clone()).transfer(refund);
+            }
+        }
+    }
+
+    fn internal_storage_balance(&self, balance: Balance) -> StorageBalance {
+        StorageBalance {
+            total: balance.into(),
+            available: balance
+                .saturating_sub(self.storage_balance_bounds().min.0)
+                .into(),
+        }
+    }
+}
+
+fn royalty_to_payout(a: u32, b: Balance) -> U128 {
+    U128(a as u128 * b / 10_000u128)
+}
+
+// near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
+// near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
+
+/// Same as `impl_non_fungible_token_approval!` except that approvals are paid like the other
+/// storage, from the attached deposit or the storage balance
+#[near_bindgen]
+impl NonFungibleTokenApproval for Contract {
+    #[payable]
+    fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: ValidAccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        assert!(
+            env::attached_deposit() > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(&token_id)
+            .expect("Token not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            owner_id,
+            "Predecessor must be token owner."
+        );
+
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let next_approval_id_by_id = self.tokens.next_approval_id_by_id.as_mut().unwrap();
+        let approval_id: u64 = next_approval_id_by_id.get(&token_id).unwrap_or(1u64);
+        let account_id: AccountId = account_id.into();
+        let mut approved_account_ids = approvals_by_id.get(&token_id).unwrap_or_default();
+        approved_account_ids.insert(account_id.clone(), approval_id);
+        approvals_by_id.insert(&token_id, &approved_account_ids);
+        next_approval_id_by_id.insert(&token_id, &(approval_id + 1));
+
+        self.internal_charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
+        msg.map(|msg| {
+            ext_approval_receiver::nft_on_approve(
+                token_id,
+                owner_id,
+                approval_id,
+                msg,
+                &account_id,
+                NO_DEPOSIT,
+                env::prepaid_gas() - GAS_FOR_NFT_APPROVE,
+            )
+        })
+    }
+
+    #[payable]
+    fn nft_revoke(&mut self, token_id: TokenId, account_id: ValidAccountId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(&token_id)
+            .expect("Token not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            owner_id,
+            "Predecessor must be token owner."
+        );
+
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        if let Some(mut approved_account_ids) = approvals_by_id.get(&token_id) {
+            if approved_account_ids.remove(account_id.as_ref()).is_some() {
+                if approved_account_ids.is_empty() {
+                    approvals_by_id.remove(&token_id);
+                } else {
+                    approvals_by_id.insert(&token_id, &approved_account_ids);
+                }
+            }
+        }
+
+        self.internal_refund_storage(&owner_id, initial_storage_usage - env::storage_usage());
+    }
+
+    #[payable]
+    fn nft_revoke_all(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(&token_id)
+            .expect("Token not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            owner_id,
+            "Predecessor must be token owner."
+        );
+
+        self.tokens
+            .approvals_by_id
+            .as_mut()
+            .unwrap()
+            .remove(&token_id);
+
+        self.internal_refund_storage(&owner_id, initial_storage_usage - env::storage_usage());
+    }
+
+    fn nft_is_approved(
+        self,
+        token_id: TokenId,
+        approved_account_id: ValidAccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        self.internal_is_approved(&token_id, approved_account_id.as_ref(), approval_id)
+    }
+}
+
+impl Contract {
+    fn internal_is_approved(
+        &self,
+        token_id: &TokenId,
+        approved_account_id: &AccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        self.tokens
+            .owner_by_id
+            .get(token_id)
+            .expect("Token not found");
+        let actual_approval_id = self
+            .tokens
+            .approvals_by_id
+            .as_ref()
+            .and_then(|by_id| by_id.get(token_id))
+            .and_then(|approvals| approvals.get(approved_account_id).cloned());
+        match (actual_approval_id, approval_id) {
+            (Some(actual_approval_id), Some(approval_id)) => actual_approval_id == approval_id,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+/// NEP-145, the balance pays for the storage of mints, series, approvals and the other calls
+/// that charge storage, when their attached deposit doesn't cover it
+#[near_bindgen]
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<ValidAccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id: AccountId = account_id
+            .map(|account_id| account_id.into())
+            .unwrap_or_else(env::predecessor_account_id);
+        let min_balance = self.storage_balance_bounds().min.0;
+        let registration_only = registration_only.unwrap_or(false);
+
+        let balance = match self.storage_deposits.get(&account_id) {
+            Some(balance) if registration_only => {
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+                balance
+            }
+            Some(balance) => balance + amount,
+            None => {
+                assert!(
+                    amount >= min_balance,
+                    "Nearlend Dao attached deposit is less than the minimum storage balance : {}",
+                    min_balance
+                );
+                if registration_only {
+                    let refund = amount - min_balance;
+                    if refund > 0 {
+                        Promise::new(env::predecessor_account_id()).transfer(refund);
+                    }
+                    min_balance
+                } else {
+                    amount
+                }
+            }
+        };
+        self.storage_deposits.insert(&account_id, &balance);
+
+        self.internal_storage_balance(balance)
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let balance = self
+            .storage_deposits
+            .get(&account_id)
+            .expect("Nearlend Dao account is not registered");
+        let available = balance.saturating_sub(self.storage_balance_bounds().min.0);
+        let amount = amount.map_or(available, |amount| amount.0);
+        assert!(
+            amount <= available,
+            "Nearlend Dao amount is more than the available storage balance : {}",
+            available
+        );
+
+        let balance = balance - amount;
+        self.storage_deposits.insert(&account_id, &balance);
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+
+        self.internal_storage_balance(balance)
+    }
+
+    /// Panics while the account owns tokens. `force` is rejected too, burning the tokens would
+    /// drop storage other accounts paid for.
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        if !self.storage_deposits.contains_key(&account_id) {
+            return false;
+        }
+        let token_count = self
+            .tokens
+            .tokens_per_owner
+            .as_ref()
+            .and_then(|tokens_per_owner| tokens_per_owner.get(&account_id))
+            .map_or(0, |token_ids| token_ids.len());
+        if token_count > 0 {
+            if force.unwrap_or(false) {
+                env::panic(b"Nearlend Dao can't force unregister, transfer or burn the tokens");
+            }
+            env::panic(b"Nearlend Dao can't unregister an account that owns tokens");
+        }
+
+        let balance = self.storage_deposits.remove(&account_id).unwrap();
+        Promise::new(account_id).transfer(balance);
+        true
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: (env::storage_byte_cost() * Balance::from(STORAGE_BALANCE_BYTES)).into(),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance> {
+        self.storage_deposits
+            .get(account_id.as_ref())
+            .map(|balance| self.internal_storage_balance(balance))
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for Contract {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        self.metadata.get().unwrap()
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Buys a token of a series priced in the calling fungible token.
+    /// `msg` is a JSON encoded `FtBuyArgs`, the unused amount is refunded to the sender.
+    /// Storage for the minted token is paid from the sender's storage balance, see
+    /// `storage_deposit`.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let ft_token_id = env::predecessor_account_id();
+        let FtBuyArgs {
+            token_series_id,
+            receiver_id,
+        } = near_sdk::serde_json::from_str(&msg).expect("Nearlend Dao invalid msg");
+
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Nearlend Dao Token series not exist");
+        assert_eq!(
+            token_series.ft_token_id,
+            Some(ft_token_id.clone()),
+            "Nearlend Dao series is not priced in {}",
+            ft_token_id
+        );
+        let initial_storage_usage = env::storage_usage();
+        let price: u128 =
+            self.internal_buy_price(&token_series_id, &token_series, sender_id.as_ref());
+        assert!(price > 0, "Nearlend Dao price must be greater than 0");
+        assert!(
+            amount.0 >= price,
+            "Nearlend Dao transferred amount is less than price : {}",
+            price
+        );
+
+        let receiver_id: AccountId = receiver_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| sender_id.to_string());
+        let token_id: TokenId = self._nft_mint_series(
+            token_series_id.clone(),
+            receiver_id.clone(),
+            None,
+            sender_id.as_ref(),
+        );
+        self.internal_charge_storage_balance(
+            sender_id.as_ref(),
+            env::storage_usage() - initial_storage_usage,
+        );
+
+        let for_treasury = price as u128
+            * self.calculate_market_data_transaction_fee(&token_series_id)
+            / 10_000u128;
+        let price_deducted = price - for_treasury;
+        internal_ft_payout(&ft_token_id, token_series.creator_id, price_deducted);
+
+        if for_treasury != 0 {
+            internal_ft_payout(&ft_token_id, self.treasury_id.clone(), for_treasury);
+        }
+
+        NearEvent::log_nft_mint(
+            receiver_id,
+            vec![token_id],
+            Some(json!({"price": price.to_string(), "ft_token_id": ft_token_id}).to_string()),
+        );
+
+        PromiseOrValue::Value(U128(amount.0 - price))
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Contract {
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        let resp: bool = self.tokens.nft_resolve_transfer(
+            previous_owner_id.clone(),
+            receiver_id.clone(),
+            token_id.clone(),
+            approved_account_ids,
+        );
+
+        // if not successful, return nft back to original owner
+        if !resp {
+            NearEvent::log_nft_transfer(receiver_id, previous_owner_id, vec![token_id], None, None);
+        }
+
+        resp
+    }
+}
+
+fn apply_edition_metadata(token_metadata: &mut TokenMetadata, edition_metadata: EditionMetadata) {
+    token_metadata.media = edition_metadata.media;
+    token_metadata.media_hash = edition_metadata.media_hash;
+    token_metadata.reference = edition_metadata.reference;
+    token_metadata.reference_hash = edition_metadata.reference_hash;
+    token_metadata.extra = edition_metadata
+        .attributes
+        .map(|attributes| json!({ "attributes": attributes }).to_string());
+}
+
+/// Adds the keys of the edition `extra` to the series `extra` when both are JSON objects,
+/// otherwise the edition `extra` wins
+fn merge_extra(series_extra: Option<String>, token_extra: Option<String>) -> Option<String> {
+    let (series_extra, token_extra) = match (series_extra, token_extra) {
+        (Some(series_extra), Some(token_extra)) => (series_extra, token_extra),
+        (series_extra, token_extra) => return token_extra.or(series_extra),
+    };
+    let series_object = near_sdk::serde_json::from_str::<
+        near_sdk::serde_json::Map<String, near_sdk::serde_json::Value>,
+    >(&series_extra);
+    let token_object = near_sdk::serde_json::from_str::<
+        near_sdk::serde_json::Map<String, near_sdk::serde_json::Value>,
+    >(&token_extra);
+    match (series_object, token_object) {
+        (Ok(mut series_object), Ok(token_object)) => {
+            series_object.extend(token_object);
+            Some(near_sdk::serde_json::Value::Object(series_object).to_string())
+        }
+        _ => Some(token_extra),
+    }
+}
+
+fn assert_valid_hash(hash: &Option<Base64VecU8>) {
+    if let Some(hash) = hash {
+        assert_eq!(hash.0.len(), 32, "Nearlend Dao hash must be 32 bytes");
+    }
+}
+
+fn assert_valid_sale_window(sale_start: Option<TimestampSec>, sale_end: Option<TimestampSec>) {
+    if let (Some(sale_start), Some(sale_end)) = (sale_start, sale_end) {
+        assert!(
+            sale_start < sale_end,
+            "Nearlend Dao sale_start must be before sale_end"
+        );
+    }
+}
+
+fn ft_account_key(ft_token_id: &AccountId, account_id: &AccountId) -> FtTokenAndAccountId {
+    format!("{}{}{}", ft_token_id, TOKEN_DELIMETER, account_id)
+}
+
+/// Transfers `amount` of `ft_token_id`, kept for a withdrawal with `ft_withdraw_payout` if it fails
+fn internal_ft_payout(ft_token_id: &AccountId, receiver_id: AccountId, amount: Balance) -> Promise {
+    ext_fungible_token::ft_transfer(
+        receiver_id.clone(),
+        U128(amount),
+        None,
+        ft_token_id,
+        ONE_YOCTO,
+        GAS_FOR_FT_TRANSFER,
+    )
+    .then(ext_self::ft_resolve_payout(
+        ft_token_id.clone(),
+        receiver_id,
+        U128(amount),
+        &env::current_account_id(),
+        NO_DEPOSIT,
+        GAS_FOR_RESOLVE_FT_PAYOUT,
+    ))
+}
+
+fn series_account_key(
+    token_series_id: &TokenSeriesId,
+    account_id: &AccountId,
+) -> TokenSeriesAndAccountId {
+    format!("{}{}{}", token_series_id, TOKEN_DELIMETER, account_id)
+}
+
+fn to_sec(timestamp: Timestamp) -> TimestampSec {
+    (timestamp / 10u64.pow(9)) as u32
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use super::*;
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use crate::event::{NearlendAdminEventKind, Nep171EventKind};
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
     use near_sdk::testing_env;
     use near_sdk::MockedBlockchain;
 
-    const STORAGE_FOR_CREATE_SERIES: Balance = 8540000000000000000000;
-    const STORAGE_FOR_MINT: Balance = 11280000000000000000000;
+    const STORAGE_FOR_CREATE_SERIES: Balance = 8540000000000000000000;
+    const STORAGE_FOR_MINT: Balance = 11280000000000000000000;
+
+    fn get_context(predecessor_account_id: ValidAccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
+
+    fn setup_contract() -> (VMContextBuilder, Contract) {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let contract = Contract::new_default_meta(accounts(0), accounts(4));
+        (context, contract)
+    }
+
+    fn setup_contract_with_fee(current_fee: u16) -> (VMContextBuilder, Contract) {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let contract = Contract::new(
+            accounts(0),
+            accounts(4),
+            NFTContractMetadata {
+                spec: NFT_METADATA_SPEC.to_string(),
+                name: "Triple Triad".to_string(),
+                symbol: "TRIAD".to_string(),
+                icon: None,
+                base_uri: None,
+                reference: None,
+                reference_hash: None,
+            },
+            current_fee,
+        );
+        (context, contract)
+    }
+
+    fn admin_events() -> Vec<NearlendAdminEventKind> {
+        get_logs()
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .filter_map(|log| match serde_json::from_str(log).unwrap() {
+                NearEvent::NearlendAdmin(event) => Some(event.event_kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_new() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let contract = Contract::new(
+            accounts(1),
+            accounts(4),
+            NFTContractMetadata {
+                spec: NFT_METADATA_SPEC.to_string(),
+                name: "Triple Triad".to_string(),
+                symbol: "TRIAD".to_string(),
+                icon: Some(DATA_IMAGE_SVG_NEARLEND_ICON.to_string()),
+                base_uri: Some("https://ipfs.fleek.co/ipfs/".to_string()),
+                reference: None,
+                reference_hash: None,
+            },
+            500,
+        );
+        testing_env!(context.is_view(true).build());
+        assert_eq!(contract.get_owner().owner_id, accounts(1).to_string());
+        assert_eq!(
+            contract.nft_metadata().base_uri.unwrap(),
+            "https://ipfs.fleek.co/ipfs/".to_string()
+        );
+        assert_eq!(
+            contract.nft_metadata().icon.unwrap(),
+            DATA_IMAGE_SVG_NEARLEND_ICON.to_string()
+        );
+    }
+
+    fn create_series(
+        contract: &mut Contract,
+        royalty: &HashMap<AccountId, u32>,
+        price: Option<U128>,
+        copies: Option<u64>,
+    ) {
+        contract.nft_create_series(
+            None,
+            TokenMetadata {
+                title: Some("Tsundere land".to_string()),
+                description: None,
+                media: Some(
+                    "bafybeidzcan4nzcz7sczs4yzyxly4galgygnbjewipj6haco4kffoqpkiy".to_string(),
+                ),
+                media_hash: None,
+                copies: copies,
+                issued_at: None,
+                expires_at: None,
+                starts_at: None,
+                updated_at: None,
+                extra: None,
+                reference: Some(
+                    "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string(),
+                ),
+                reference_hash: None,
+            },
+            price,
+            Some(royalty.clone()),
+            None,
+        );
+    }
+
+    fn create_ft_series(contract: &mut Contract, price: U128, ft_token_id: ValidAccountId) {
+        contract.nft_create_series(
+            None,
+            TokenMetadata {
+                title: Some("Tsundere land".to_string()),
+                description: None,
+                media: None,
+                media_hash: None,
+                copies: None,
+                issued_at: None,
+                expires_at: None,
+                starts_at: None,
+                updated_at: None,
+                extra: None,
+                reference: None,
+                reference_hash: None,
+            },
+            Some(price),
+            None,
+            Some(SaleConfig {
+                ft_token_id: Some(ft_token_id),
+                ..Default::default()
+            }),
+        );
+    }
+
+    #[test]
+    fn test_create_series() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        let nft_series_return = contract.nft_get_series_single("1".to_string());
+        assert_eq!(nft_series_return.creator_id, accounts(1).to_string());
+
+        assert_eq!(nft_series_return.token_series_id, "1",);
+
+        assert_eq!(nft_series_return.royalty, royalty,);
+
+        assert!(nft_series_return.metadata.copies.is_none());
+
+        assert_eq!(
+            nft_series_return.metadata.title.unwrap(),
+            "Tsundere land".to_string()
+        );
+
+        assert_eq!(
+            nft_series_return.metadata.reference.unwrap(),
+            "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string()
+        );
+    }
+
+    #[test]
+    fn test_buy() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_buy("1".to_string(), accounts(2));
+
+        let token_from_nft_token = contract.nft_token(token_id);
+        assert_eq!(
+            token_from_nft_token.unwrap().owner_id,
+            accounts(2).to_string()
+        )
+    }
+
+    #[test]
+    fn test_ft_buy() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_ft_series(&mut contract, U128::from(100), accounts(3));
+
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(min_balance + STORAGE_FOR_MINT)
+            .build());
+        contract.storage_deposit(None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(0)
+            .build());
+
+        let refund = contract.ft_on_transfer(
+            accounts(2),
+            U128::from(150),
+            json!({ "token_series_id": "1" }).to_string(),
+        );
+        match refund {
+            PromiseOrValue::Value(refund) => assert_eq!(refund, U128::from(50)),
+            PromiseOrValue::Promise(_) => panic!("expected a value"),
+        }
+
+        let token = contract
+            .nft_token(format!("1{}1", TOKEN_DELIMETER))
+            .unwrap();
+        assert_eq!(token.owner_id, accounts(2).to_string());
+        assert!(
+            contract
+                .storage_balance_of(accounts(2))
+                .unwrap()
+                .available
+                .0
+                < STORAGE_FOR_MINT
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must add")]
+    fn test_invalid_ft_buy_without_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_ft_series(&mut contract, U128::from(100), accounts(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(0)
+            .build());
+
+        contract.ft_on_transfer(
+            accounts(2),
+            U128::from(100),
+            json!({ "token_series_id": "1" }).to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao price must be greater than 0")]
+    fn test_invalid_create_ft_series_free() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_ft_series(&mut contract, U128::from(0), accounts(3));
+    }
+
+    #[test]
+    fn test_ft_payout_kept_when_transfer_fails() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+
+        contract.ft_resolve_payout(accounts(3).to_string(), accounts(1).to_string(), U128(95));
+        contract.ft_resolve_payout(accounts(3).to_string(), accounts(1).to_string(), U128(5));
+        assert_eq!(contract.ft_payout_of(accounts(3), accounts(1)), U128(100));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.ft_withdraw_payout(accounts(3));
+        assert_eq!(contract.ft_payout_of(accounts(3), accounts(1)), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao series is not priced in")]
+    fn test_invalid_ft_buy_wrong_token() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_ft_series(&mut contract, U128::from(100), accounts(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .build());
+
+        contract.ft_on_transfer(
+            accounts(2),
+            U128::from(100),
+            json!({ "token_series_id": "1" }).to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao series is priced in")]
+    fn test_invalid_buy_ft_series_with_near() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_ft_series(&mut contract, U128::from(100), accounts(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_buy("1".to_string(), accounts(2));
+    }
+
+    #[test]
+    fn test_presale_buy() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_presale("1".to_string(), Some(1_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+        contract.nft_add_series_presale_allowlist(
+            "1".to_string(),
+            vec![PresaleAllowlistEntry {
+                account_id: accounts(2),
+                quota: 1,
+                price: Some(U128::from(5 * 10u128.pow(23))),
+            }],
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(5 * 10u128.pow(23) + STORAGE_FOR_MINT)
+            .build());
+        let token_id = contract.nft_buy("1".to_string(), accounts(2));
+        assert_eq!(
+            contract.nft_token(token_id).unwrap().owner_id,
+            accounts(2).to_string()
+        );
+
+        let allowance = contract
+            .nft_get_series_presale_allowance("1".to_string(), accounts(2))
+            .unwrap();
+        assert_eq!(allowance.minted, 1);
+
+        // public sale opens to everyone at the series price
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(1_000 * 10u64.pow(9))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        let token_id = contract.nft_buy("1".to_string(), accounts(3));
+        assert_eq!(
+            contract.nft_token(token_id).unwrap().owner_id,
+            accounts(3).to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao presale is only for allowlisted accounts")]
+    fn test_invalid_presale_buy_not_allowlisted() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_presale("1".to_string(), Some(1_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        contract.nft_buy("1".to_string(), accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao presale quota exceeded : 1")]
+    fn test_invalid_presale_buy_above_quota() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_presale("1".to_string(), Some(1_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+        contract.nft_add_series_presale_allowlist(
+            "1".to_string(),
+            vec![PresaleAllowlistEntry {
+                account_id: accounts(2),
+                quota: 1,
+                price: None,
+            }],
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        contract.nft_buy("1".to_string(), accounts(2));
+        contract.nft_buy("1".to_string(), accounts(2));
+    }
+
+    #[test]
+    fn test_sale_window() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_sale_window("1".to_string(), Some(1_000), Some(2_000));
+        assert!(!contract.nft_get_series_single("1".to_string()).is_sale_open);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(1_500 * 10u64.pow(9))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        let series = contract.nft_get_series_single("1".to_string());
+        assert!(series.is_sale_open);
+        assert_eq!(series.sale_start, Some(1_000));
+        assert_eq!(series.sale_end, Some(2_000));
+
+        let token_id = contract.nft_buy("1".to_string(), accounts(2));
+        assert_eq!(
+            contract.nft_token(token_id).unwrap().owner_id,
+            accounts(2).to_string()
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .block_timestamp(1_500 * 10u64.pow(9))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_price("1".to_string(), None);
+        assert!(!contract.nft_get_series_single("1".to_string()).is_sale_open);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao sale is not open")]
+    fn test_invalid_buy_after_sale_end() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_sale_window("1".to_string(), None, Some(2_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(2_000 * 10u64.pow(9))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        contract.nft_buy("1".to_string(), accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao max per account reached : 2")]
+    fn test_invalid_buy_above_max_per_account() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_max_per_account("1".to_string(), Some(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        contract.nft_buy("1".to_string(), accounts(2));
+        contract.nft_buy("1".to_string(), accounts(3));
+        assert_eq!(
+            contract.nft_get_series_minted_by_account("1".to_string(), accounts(2)),
+            2
+        );
+
+        contract.nft_buy("1".to_string(), accounts(2));
+    }
+
+    #[test]
+    fn test_dutch_auction_price() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_price_strategy(
+            "1".to_string(),
+            Some(PriceStrategy::DutchAuction {
+                start_price: U128::from(10 * 10u128.pow(24)),
+                floor_price: U128::from(2 * 10u128.pow(24)),
+                start_time: 1_000,
+                decay_interval: 60,
+                decay_amount: U128::from(1 * 10u128.pow(24)),
+            }),
+        );
+        assert_eq!(
+            contract.nft_get_series_current_price("1".to_string()),
+            Some(U128::from(10 * 10u128.pow(24)))
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(1_150 * 10u64.pow(9))
+            .attached_deposit(10 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        assert_eq!(
+            contract.nft_get_series_current_price("1".to_string()),
+            Some(U128::from(8 * 10u128.pow(24)))
+        );
+        let token_id = contract.nft_buy("1".to_string(), accounts(2));
+        assert_eq!(
+            contract.nft_token(token_id).unwrap().owner_id,
+            accounts(2).to_string()
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(100_000 * 10u64.pow(9))
+            .build());
+        assert_eq!(
+            contract.nft_get_series_current_price("1".to_string()),
+            Some(U128::from(2 * 10u128.pow(24)))
+        );
+    }
+
+    #[test]
+    fn test_dutch_auction_buy_refunds_excess() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_price_strategy(
+            "1".to_string(),
+            Some(PriceStrategy::DutchAuction {
+                start_price: U128::from(10 * 10u128.pow(24)),
+                floor_price: U128::from(2 * 10u128.pow(24)),
+                start_time: 1_000,
+                decay_interval: 60,
+                decay_amount: U128::from(1 * 10u128.pow(24)),
+            }),
+        );
+
+        let decayed_price = 8 * 10u128.pow(24);
+        let attached_deposit = 10 * 10u128.pow(24) + STORAGE_FOR_MINT;
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(1_150 * 10u64.pow(9))
+            .attached_deposit(attached_deposit)
+            .build());
+        let initial_storage_usage = env::storage_usage();
+        contract.nft_buy("1".to_string(), accounts(2));
+        let storage_cost =
+            env::storage_byte_cost() * Balance::from(env::storage_usage() - initial_storage_usage);
+
+        let refund = attached_deposit - decayed_price - storage_cost;
+        let receipts: Vec<String> = get_created_receipts()
+            .iter()
+            .map(|receipt| serde_json::to_string(receipt).unwrap())
+            .collect();
+        assert!(receipts.contains(&format!(
+            r#"{{"receipt_indices":[],"receiver_id":"{}","actions":[{{"Transfer":{{"deposit":{}}}}}]}}"#,
+            accounts(2),
+            refund
+        )));
+
+        let mint_memo = get_logs()
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .find_map(|log| match serde_json::from_str(log).unwrap() {
+                NearEvent::Nep171(event) => match event.event_kind {
+                    Nep171EventKind::NftMint(mut data) => data.remove(0).memo,
+                    _ => None,
+                },
+                _ => None,
+            });
+        assert_eq!(
+            mint_memo,
+            Some(json!({"price": decayed_price.to_string()}).to_string())
+        );
+    }
+
+    #[test]
+    fn test_bonding_curve_price() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_price_strategy(
+            "1".to_string(),
+            Some(PriceStrategy::LinearCurve {
+                base_price: U128::from(1 * 10u128.pow(24)),
+                increment: U128::from(5 * 10u128.pow(23)),
+            }),
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(2 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+        contract.nft_buy("1".to_string(), accounts(2));
+
+        assert_eq!(
+            contract.nft_get_series_price_quote("1".to_string(), 10),
+            vec![
+                U128::from(15 * 10u128.pow(23)),
+                U128::from(2 * 10u128.pow(24))
+            ]
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_price_strategy(
+            "1".to_string(),
+            Some(PriceStrategy::ExponentialCurve {
+                base_price: U128::from(1 * 10u128.pow(24)),
+                growth_bps: 1_000,
+            }),
+        );
+        assert_eq!(
+            contract.nft_get_series_price_quote("1".to_string(), 2),
+            vec![
+                U128::from(11 * 10u128.pow(23)),
+                U128::from(121 * 10u128.pow(22))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_price_quote_is_bounded() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        let price_strategy = PriceStrategy::ExponentialCurve {
+            base_price: U128::from(1 * 10u128.pow(24)),
+            growth_bps: 1,
+        };
+        contract.nft_set_series_price_strategy("1".to_string(), Some(price_strategy.clone()));
+
+        assert_eq!(
+            contract
+                .nft_get_series_price_quote("1".to_string(), u64::MAX)
+                .len() as u64,
+            MAX_PRICE_QUOTE
+        );
+        assert_eq!(price_strategy.price_at(0, u64::MAX), MAX_PRICE);
+        // 1.0001^10_000 = 2.718145926...
+        assert_eq!(
+            price_strategy.price_at(0, 10_000) / 10u128.pow(15),
+            2_718_145_926
+        );
+    }
+
+    #[test]
+    fn test_auction() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, Some(1));
+
+        contract.nft_create_auction(
+            "1".to_string(),
+            U128::from(1 * 10u128.pow(24)),
+            U128::from(1 * 10u128.pow(23)),
+            1_000,
+            300,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(100 * 10u64.pow(9))
+            .attached_deposit(1 * 10u128.pow(24))
+            .build());
+        contract.nft_auction_bid("1".to_string());
+
+        // outbid close to the end extends the auction
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(900 * 10u64.pow(9))
+            .attached_deposit(2 * 10u128.pow(24))
+            .build());
+        let auction = contract.nft_auction_bid("1".to_string());
+        assert_eq!(auction.bidder_id, Some(accounts(3).to_string()));
+        assert_eq!(auction.end_time, 1_200);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .block_timestamp(1_200 * 10u64.pow(9))
+            .attached_deposit(0)
+            .build());
+        let token_id = contract.nft_settle_auction("1".to_string()).unwrap();
+        assert_eq!(
+            contract.nft_token(token_id).unwrap().owner_id,
+            accounts(3).to_string()
+        );
+        assert!(contract.nft_get_auction("1".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_auction_settle_refunds_when_mint_is_paused() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, Some(1));
+
+        contract.nft_create_auction(
+            "1".to_string(),
+            U128::from(1 * 10u128.pow(24)),
+            U128::from(1 * 10u128.pow(23)),
+            1_000,
+            300,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(100 * 10u64.pow(9))
+            .attached_deposit(1 * 10u128.pow(24))
+            .build());
+        contract.nft_auction_bid("1".to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.set_paused(PauseOperation::Mint, true);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .block_timestamp(1_000 * 10u64.pow(9))
+            .attached_deposit(0)
+            .build());
+        assert!(contract.nft_settle_auction("1".to_string()).is_none());
+        assert!(contract.nft_get_auction("1".to_string()).is_none());
+        assert_eq!(contract.nft_supply_for_series("1".to_string()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao series is on auction")]
+    fn test_invalid_mint_on_auction() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, Some(1));
+
+        contract.nft_create_auction(
+            "1".to_string(),
+            U128::from(1 * 10u128.pow(24)),
+            U128::from(1 * 10u128.pow(23)),
+            1_000,
+            300,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+        contract.nft_mint("1".to_string(), accounts(2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao reserve_price must be positive")]
+    fn test_invalid_auction_zero_reserve_price() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, Some(1));
+
+        contract.nft_create_auction(
+            "1".to_string(),
+            U128::from(0),
+            U128::from(1 * 10u128.pow(23)),
+            1_000,
+            300,
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Nearlend Dao bid is less than minimum bid : 1100000000000000000000000"
+    )]
+    fn test_invalid_auction_bid_below_increment() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, Some(1));
+
+        contract.nft_create_auction(
+            "1".to_string(),
+            U128::from(1 * 10u128.pow(24)),
+            U128::from(1 * 10u128.pow(23)),
+            1_000,
+            300,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24))
+            .build());
+        contract.nft_auction_bid("1".to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1 * 10u128.pow(24) + 1)
+            .build());
+        contract.nft_auction_bid("1".to_string());
+    }
+
+    #[test]
+    fn test_mint() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        let token_from_nft_token = contract.nft_token(token_id);
+        assert_eq!(
+            token_from_nft_token.unwrap().owner_id,
+            accounts(2).to_string()
+        )
+    }
+
+    #[test]
+    fn test_mint_batch() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 3)
+            .build());
+
+        let result = contract.nft_mint_batch(
+            "1".to_string(),
+            vec![
+                BatchMintReceiver {
+                    receiver_id: accounts(2),
+                    count: Some(2),
+                },
+                BatchMintReceiver {
+                    receiver_id: accounts(3),
+                    count: None,
+                },
+            ],
+        );
+
+        assert!(result.completed);
+        assert_eq!(result.processed, 2);
+        assert_eq!(result.token_ids, vec!["1:1", "1:2", "1:3"]);
+        assert_eq!(
+            contract.nft_token("1:3".to_string()).unwrap().owner_id,
+            accounts(3).to_string()
+        );
+    }
+
+    #[test]
+    fn test_mint_batch_stops_before_running_out_of_gas() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, None);
+
+        // enough for one token, every mint uses more than the remaining 1 Tgas
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 3)
+            .prepaid_gas(GAS_FOR_BATCH_MINT_FINISH + GAS_FOR_BATCH_MINT_TOKEN + 1_000_000_000_000)
+            .build());
+
+        let result = contract.nft_mint_batch(
+            "1".to_string(),
+            vec![BatchMintReceiver {
+                receiver_id: accounts(2),
+                count: Some(3),
+            }],
+        );
+        assert!(!result.completed);
+        assert_eq!(result.processed, 0);
+        assert_eq!(result.partial_count, 1);
+        assert_eq!(result.token_ids, vec!["1:1".to_string()]);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 2)
+            .prepaid_gas(300_000_000_000_000)
+            .build());
+
+        let result = contract.nft_mint_batch(
+            "1".to_string(),
+            vec![BatchMintReceiver {
+                receiver_id: accounts(2),
+                count: Some(2),
+            }],
+        );
+        assert!(result.completed);
+        assert_eq!(result.partial_count, 0);
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128::from(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao batch is more than the remaining copies : 1")]
+    fn test_invalid_mint_batch_above_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(1));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 2)
+            .build());
+
+        contract.nft_mint_batch(
+            "1".to_string(),
+            vec![BatchMintReceiver {
+                receiver_id: accounts(2),
+                count: Some(2),
+            }],
+        );
+    }
+
+    fn edition_metadata(media: &str, rarity: &str) -> EditionMetadata {
+        EditionMetadata {
+            media: Some(media.to_string()),
+            media_hash: None,
+            reference: None,
+            reference_hash: None,
+            attributes: Some(vec![Attribute {
+                trait_type: "rarity".to_string(),
+                value: rarity.to_string(),
+            }]),
+        }
+    }
+
+    #[test]
+    fn test_nft_mint_with_edition_metadata() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 2)
+            .build());
+
+        let token_id = contract.nft_mint(
+            "1".to_string(),
+            accounts(2),
+            Some(edition_metadata("edition-1.png", "legendary")),
+        );
+
+        let metadata = contract.nft_token(token_id).unwrap().metadata.unwrap();
+        assert_eq!(metadata.media, Some("edition-1.png".to_string()));
+        assert_eq!(
+            metadata.reference,
+            Some("bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string())
+        );
+        assert_eq!(metadata.copies, Some(2));
+        assert_eq!(
+            metadata.extra,
+            Some(r#"{"attributes":[{"trait_type":"rarity","value":"legendary"}]}"#.to_string())
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+        let metadata = contract.nft_token(token_id).unwrap().metadata.unwrap();
+        assert_eq!(
+            metadata.media,
+            Some("bafybeidzcan4nzcz7sczs4yzyxly4galgygnbjewipj6haco4kffoqpkiy".to_string())
+        );
+        assert_eq!(metadata.extra, None);
+    }
+
+    #[test]
+    fn test_nft_set_edition_metadata() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_set_edition_metadata(
+            "1".to_string(),
+            vec![EditionMetadataEntry {
+                edition: 2,
+                metadata: edition_metadata("edition-2.png", "rare"),
+            }],
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 2)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        let metadata = contract
+            .nft_token(token_id.clone())
+            .unwrap()
+            .metadata
+            .unwrap();
+        assert_eq!(metadata.media, Some("edition-2.png".to_string()));
+        assert_eq!(
+            metadata.extra,
+            Some(r#"{"attributes":[{"trait_type":"rarity","value":"rare"}]}"#.to_string())
+        );
+        assert!(contract.edition_metadata.get(&token_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao edition 1 is already minted")]
+    fn test_invalid_set_edition_metadata_minted() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 2)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_set_edition_metadata(
+            "1".to_string(),
+            vec![EditionMetadataEntry {
+                edition: 1,
+                metadata: edition_metadata("edition-1.png", "rare"),
+            }],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao edition starts at 1")]
+    fn test_invalid_set_edition_metadata_zero() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_set_edition_metadata(
+            "1".to_string(),
+            vec![EditionMetadataEntry {
+                edition: 0,
+                metadata: edition_metadata("edition-0.png", "rare"),
+            }],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao hash must be 32 bytes")]
+    fn test_invalid_set_edition_metadata_hash() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let mut metadata = edition_metadata("edition-1.png", "rare");
+        metadata.media_hash = Some(Base64VecU8::from(vec![0u8; 3]));
+        contract.nft_set_edition_metadata(
+            "1".to_string(),
+            vec![EditionMetadataEntry {
+                edition: 1,
+                metadata,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_decrease_copies_removes_edition_metadata() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_set_edition_metadata(
+            "1".to_string(),
+            vec![
+                EditionMetadataEntry {
+                    edition: 2,
+                    metadata: edition_metadata("edition-2.png", "rare"),
+                },
+                EditionMetadataEntry {
+                    edition: 3,
+                    metadata: edition_metadata("edition-3.png", "common"),
+                },
+            ],
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_decrease_series_copies("1".to_string(), U64::from(1));
+        assert!(contract.edition_metadata.get(&"1:2".to_string()).is_some());
+        assert!(contract.edition_metadata.get(&"1:3".to_string()).is_none());
+    }
+
+    fn reveal_commitment(revealed: &str) -> Base58CryptoHash {
+        let hash: [u8; 32] = env::sha256(revealed.as_bytes()).try_into().unwrap();
+        Base58CryptoHash::from(hash)
+    }
+
+    fn create_series_with_reveal(
+        context: &mut VMContextBuilder,
+        contract: &mut Contract,
+        copies: u64,
+        revealed: &str,
+    ) {
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(contract, &HashMap::new(), None, Some(copies));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_set_series_reveal_commitment("1".to_string(), reveal_commitment(revealed));
+    }
+
+    #[test]
+    fn test_nft_reveal_series_cid() {
+        let (mut context, mut contract) = setup_contract();
+        create_series_with_reveal(&mut context, &mut contract, 3, "bafyreveal:42");
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 3)
+            .build());
+
+        for _ in 0..3 {
+            contract.nft_mint("1".to_string(), accounts(2), None);
+        }
+        assert_eq!(
+            contract
+                .nft_token("1:1".to_string())
+                .unwrap()
+                .metadata
+                .unwrap()
+                .reference,
+            Some("bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string())
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_reveal_series_cid("1".to_string(), "bafyreveal".to_string(), "42".to_string());
+
+        let mut references: Vec<String> = ["1:1", "1:2", "1:3"]
+            .iter()
+            .map(|token_id| {
+                let token = contract.nft_token(token_id.to_string()).unwrap();
+                token.metadata.unwrap().reference.unwrap()
+            })
+            .collect();
+        references.sort();
+        assert_eq!(
+            references,
+            vec![
+                "bafyreveal/1.json",
+                "bafyreveal/2.json",
+                "bafyreveal/3.json"
+            ]
+        );
+        assert!(contract.nft_get_series_single("1".to_string()).is_revealed);
+    }
+
+    #[test]
+    fn test_nft_reveal_series_manifest() {
+        let manifest = r#"[{"media":"edition-1.png","attributes":[{"trait_type":"rarity","value":"rare"}]},{"media":"edition-2.png"}]"#;
+        let (mut context, mut contract) = setup_contract();
+        create_series_with_reveal(&mut context, &mut contract, 2, manifest);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 2)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_mint("1".to_string(), accounts(3), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_reveal_series_manifest("1".to_string(), manifest.to_string());
+
+        let metadata = contract
+            .nft_token("1:1".to_string())
+            .unwrap()
+            .metadata
+            .unwrap();
+        assert_eq!(metadata.media, Some("edition-1.png".to_string()));
+        assert_eq!(
+            metadata.extra,
+            Some(r#"{"attributes":[{"trait_type":"rarity","value":"rare"}]}"#.to_string())
+        );
+        assert_eq!(
+            contract
+                .nft_token("1:2".to_string())
+                .unwrap()
+                .metadata
+                .unwrap()
+                .media,
+            Some("edition-2.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_nft_reveal_series_manifest_after_non_mintable() {
+        let manifest =
+            r#"[{"media":"edition-1.png"},{"media":"edition-2.png"},{"media":"edition-3.png"}]"#;
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_set_series_reveal_commitment("1".to_string(), reveal_commitment(manifest));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_set_series_non_mintable("1".to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_reveal_series_manifest("1".to_string(), manifest.to_string());
+
+        assert_eq!(
+            contract
+                .nft_token("1:1".to_string())
+                .unwrap()
+                .metadata
+                .unwrap()
+                .media,
+            Some("edition-1.png".to_string())
+        );
+        assert!(contract.nft_token("1:2".to_string()).is_none());
+        assert!(contract.nft_get_series_single("1".to_string()).is_revealed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao series is still mintable")]
+    fn test_invalid_reveal_series_while_mintable() {
+        let (mut context, mut contract) = setup_contract();
+        create_series_with_reveal(&mut context, &mut contract, 3, "bafyreveal:42");
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_reveal_series_cid("1".to_string(), "bafyreveal".to_string(), "42".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao reveal does not match the commitment")]
+    fn test_invalid_reveal_series_cid_mismatch() {
+        let (mut context, mut contract) = setup_contract();
+        create_series_with_reveal(&mut context, &mut contract, 1, "bafyreveal:42");
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_reveal_series_cid("1".to_string(), "bafyreveal".to_string(), "43".to_string());
+    }
+
+    #[test]
+    fn test_nft_mint_random_editions() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_set_series_random_editions("1".to_string(), true);
+        assert!(
+            contract
+                .nft_get_series_single("1".to_string())
+                .is_random_editions
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 5)
+            .random_seed(vec![7; 32])
+            .build());
+
+        let mut token_ids: Vec<TokenId> = (0..5)
+            .map(|_| contract.nft_mint("1".to_string(), accounts(2), None))
+            .collect();
+        assert_ne!(token_ids, vec!["1:1", "1:2", "1:3", "1:4", "1:5"]);
+        token_ids.sort();
+        assert_eq!(token_ids, vec!["1:1", "1:2", "1:3", "1:4", "1:5"]);
+
+        let token_series = contract.token_series_by_id.get(&"1".to_string()).unwrap();
+        assert!(!token_series.is_mintable);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao cannot decrease supply of a random edition series")]
+    fn test_invalid_decrease_copies_random_editions() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_set_series_random_editions("1".to_string(), true);
+        contract.nft_decrease_series_copies("1".to_string(), U64::from(2));
+    }
+
+    #[test]
+    fn test_nft_update_series_metadata() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .block_timestamp(1_000)
+            .build());
+
+        contract.nft_update_series_metadata(
+            "1".to_string(),
+            SeriesMetadataUpdate {
+                description: Some("Tsundere land, fixed".to_string()),
+                media: Some("updated.png".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let series_metadata = contract.nft_get_series_single("1".to_string()).metadata;
+        assert_eq!(
+            series_metadata.description,
+            Some("Tsundere land, fixed".to_string())
+        );
+        assert_eq!(series_metadata.updated_at, Some("1000".to_string()));
+        assert_eq!(
+            series_metadata.reference,
+            Some("bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string())
+        );
+        assert_eq!(
+            contract
+                .nft_token(token_id)
+                .unwrap()
+                .metadata
+                .unwrap()
+                .media,
+            Some("updated.png".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao series metadata is frozen")]
+    fn test_invalid_update_frozen_series_metadata() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_freeze_series_metadata("1".to_string());
+        assert!(
+            contract
+                .nft_get_series_single("1".to_string())
+                .is_metadata_frozen
+        );
+
+        contract.nft_update_series_metadata(
+            "1".to_string(),
+            SeriesMetadataUpdate {
+                description: Some("Tsundere land, fixed".to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_update_contract_metadata() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+
+        contract.update_contract_metadata(ContractMetadataUpdate {
+            base_uri: Some("https://ipfs.io/ipfs".to_string()),
+            ..Default::default()
+        });
+
+        let metadata = contract.nft_metadata();
+        assert_eq!(metadata.base_uri, Some("https://ipfs.io/ipfs".to_string()));
+        assert_eq!(metadata.name, "Lang Biang Collectibles".to_string());
+        assert_eq!(metadata.symbol, "NLLANG".to_string());
+        assert!(matches!(
+            admin_events().as_slice(),
+            [NearlendAdminEventKind::ContractMetadataUpdate(data)]
+                if data[0].authorized_id == accounts(0).to_string()
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao Owner only")]
+    fn test_invalid_update_contract_metadata_not_owner() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.update_contract_metadata(ContractMetadataUpdate {
+            name: Some("Nearlend Collectibles".to_string()),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao Token series is not mintable")]
+    fn test_invalid_mint_non_mintable() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_set_series_non_mintable("1".to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao Token series is not mintable")]
+    fn test_invalid_mint_above_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, Some(1));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_mint("1".to_string(), accounts(2), None);
+    }
+
+    #[test]
+    fn test_decrease_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
 
-    fn get_context(predecessor_account_id: ValidAccountId) -> VMContextBuilder {
-        let mut builder = VMContextBuilder::new();
-        builder
-            .current_account_id(accounts(0))
-            .signer_account_id(predecessor_account_id.clone())
-            .predecessor_account_id(predecessor_account_id);
-        builder
+        create_series(&mut contract, &royalty, None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_decrease_series_copies("1".to_string(), U64::from(3));
     }
 
-    fn setup_contract() -> (VMContextBuilder, Contract) {
-        let mut context = VMContextBuilder::new();
-        testing_env!(context.predecessor_account_id(accounts(0)).build());
-        let contract = Contract::new_default_meta(accounts(0), accounts(4));
-        (context, contract)
+    #[test]
+    #[should_panic(expected = "Nearlend Dao cannot decrease supply, already minted : 2")]
+    fn test_invalid_decrease_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_decrease_series_copies("1".to_string(), U64::from(4));
     }
 
     #[test]
-    fn test_new() {
-        let mut context = get_context(accounts(1));
-        testing_env!(context.build());
-        let contract = Contract::new(
-            accounts(1),
-            accounts(4),
-            NFTContractMetadata {
-                spec: NFT_METADATA_SPEC.to_string(),
-                name: "Triple Triad".to_string(),
-                symbol: "TRIAD".to_string(),
-                icon: Some(DATA_IMAGE_SVG_NEARLEND_ICON.to_string()),
-                base_uri: Some("https://ipfs.fleek.co/ipfs/".to_string()),
-                reference: None,
-                reference_hash: None,
-            },
-            500,
+    #[should_panic(expected = "Nearlend Dao not for sale")]
+    fn test_invalid_buy_price_null() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
         );
-        testing_env!(context.is_view(true).build());
-        assert_eq!(contract.get_owner(), accounts(1).to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_set_series_price("1".to_string(), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_buy("1".to_string(), accounts(2));
+
+        let token_from_nft_token = contract.nft_token(token_id);
         assert_eq!(
-            contract.nft_metadata().base_uri.unwrap(),
-            "https://ipfs.fleek.co/ipfs/".to_string()
+            token_from_nft_token.unwrap().owner_id,
+            accounts(2).to_string()
+        )
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao price higher than 1000000000000000000000000000000000")]
+    fn test_invalid_price_shouldnt_be_higher_than_max_price() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1_000_000_000 * 10u128.pow(24))),
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+    }
+
+    #[test]
+    fn test_nft_burn() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_burn(token_id.clone(), None);
+        let token = contract.nft_token(token_id);
+        assert!(token.is_none());
+    }
+
+    #[test]
+    fn test_nft_burn_remove_from_series() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+        contract.nft_mint("1".to_string(), accounts(2), None);
+
+        let creator_available = contract.storage_deposit(None, None).available.0;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let available = contract.storage_deposit(None, None).available.0;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_burn(token_id, Some(true));
+        assert_eq!(contract.nft_supply_for_series("1".to_string()).0, 1);
+        // the creator paid the storage of the minted token, not the owner burning it
+        assert!(
+            contract
+                .storage_balance_of(accounts(1))
+                .unwrap()
+                .available
+                .0
+                > creator_available
         );
         assert_eq!(
-            contract.nft_metadata().icon.unwrap(),
-            DATA_IMAGE_SVG_NEARLEND_ICON.to_string()
+            contract
+                .storage_balance_of(accounts(2))
+                .unwrap()
+                .available
+                .0,
+            available
         );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        // edition numbers and copies still count the burned token
+        assert_eq!(contract.nft_mint("1".to_string(), accounts(2), None), "1:3");
+        assert!(!contract.nft_get_series_single("1".to_string()).is_sale_open);
     }
 
-    fn create_series(
-        contract: &mut Contract,
-        royalty: &HashMap<AccountId, u32>,
-        price: Option<U128>,
-        copies: Option<u64>,
-    ) {
-        contract.nft_create_series(
-            None,
-            TokenMetadata {
-                title: Some("Tsundere land".to_string()),
-                description: None,
-                media: Some(
-                    "bafybeidzcan4nzcz7sczs4yzyxly4galgygnbjewipj6haco4kffoqpkiy".to_string(),
-                ),
-                media_hash: None,
-                copies: copies,
-                issued_at: None,
-                expires_at: None,
-                starts_at: None,
-                updated_at: None,
-                extra: None,
-                reference: Some(
-                    "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string(),
-                ),
-                reference_hash: None,
-            },
-            price,
-            Some(royalty.clone()),
-        );
+    #[test]
+    fn test_nft_transfer() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_transfer(accounts(3), token_id.clone(), None, None);
+
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(token.owner_id, accounts(3).to_string())
     }
 
-    #[test]
-    fn test_create_series() {
-        let (mut context, mut contract) = setup_contract();
+    fn setup_locked_token(context: &mut VMContextBuilder, contract: &mut Contract) -> TokenId {
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-        create_series(
-            &mut contract,
-            &royalty,
-            Some(U128::from(1 * 10u128.pow(24))),
-            None,
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_lock(
+            token_id.clone(),
+            accounts(4),
+            Some("collateral".to_string()),
         );
+        assert!(contract.nft_is_locked(token_id.clone()));
 
-        let nft_series_return = contract.nft_get_series_single("1".to_string());
-        assert_eq!(nft_series_return.creator_id, accounts(1).to_string());
+        token_id
+    }
 
-        assert_eq!(nft_series_return.token_series_id, "1",);
+    #[test]
+    #[should_panic(expected = "Nearlend Dao token is locked by")]
+    fn test_invalid_nft_transfer_locked() {
+        let (mut context, mut contract) = setup_contract();
+        let token_id = setup_locked_token(&mut context, &mut contract);
 
-        assert_eq!(nft_series_return.royalty, royalty,);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
 
-        assert!(nft_series_return.metadata.copies.is_none());
+        contract.nft_transfer(accounts(3), token_id, None, None);
+    }
 
-        assert_eq!(
-            nft_series_return.metadata.title.unwrap(),
-            "Tsundere land".to_string()
-        );
+    #[test]
+    fn test_nft_transfer_by_locker() {
+        let (mut context, mut contract) = setup_contract();
+        let token_id = setup_locked_token(&mut context, &mut contract);
 
-        assert_eq!(
-            nft_series_return.metadata.reference.unwrap(),
-            "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string()
-        );
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_transfer(accounts(4), token_id.clone(), None, None);
+
+        let token = contract.nft_token(token_id.clone()).unwrap();
+        assert_eq!(token.owner_id, accounts(4).to_string());
+        assert!(!contract.nft_is_locked(token_id));
     }
 
     #[test]
-    fn test_buy() {
+    fn test_nft_set_user() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
 
-        create_series(
-            &mut contract,
-            &royalty,
-            Some(U128::from(1 * 10u128.pow(24))),
-            None,
-        );
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
 
         testing_env!(context
             .predecessor_account_id(accounts(2))
-            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .attached_deposit(STORAGE_FOR_MINT)
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build());
 
-        let token_id = contract.nft_buy("1".to_string(), accounts(2));
+        contract.nft_set_user(token_id.clone(), accounts(3), 2_000);
 
-        let token_from_nft_token = contract.nft_token(token_id);
-        assert_eq!(
-            token_from_nft_token.unwrap().owner_id,
-            accounts(2).to_string()
-        )
+        let token = contract.nft_token(token_id.clone()).unwrap();
+        assert_eq!(token.owner_id, accounts(2).to_string());
+        assert_eq!(token.user_id, Some(accounts(3).to_string()));
+        assert_eq!(token.user_expires, Some(2_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(0)
+            .block_timestamp(2_000 * 10u64.pow(9))
+            .build());
+
+        assert!(contract
+            .nft_token(token_id.clone())
+            .unwrap()
+            .user_id
+            .is_none());
     }
 
     #[test]
-    fn test_mint() {
+    #[should_panic(expected = "Requires attached deposit of at least 1 yoctoNEAR")]
+    fn test_invalid_nft_set_user_without_deposit() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
         create_series(&mut contract, &royalty, None, None);
 
         testing_env!(context
@@ -1502,98 +6482,116 @@ mod tests {
             .attached_deposit(STORAGE_FOR_MINT)
             .build());
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
 
-        let token_from_nft_token = contract.nft_token(token_id);
-        assert_eq!(
-            token_from_nft_token.unwrap().owner_id,
-            accounts(2).to_string()
-        )
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+
+        contract.nft_set_user(token_id, accounts(3), 2_000);
     }
 
     #[test]
-    #[should_panic(expected = "Nearlend Dao Token series is not mintable")]
-    fn test_invalid_mint_non_mintable() {
+    #[should_panic(expected = "Requires attached deposit of at least 1 yoctoNEAR")]
+    fn test_invalid_nft_lock_without_deposit() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
         create_series(&mut contract, &royalty, None, None);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
-            .attached_deposit(1)
+            .attached_deposit(STORAGE_FOR_MINT)
             .build());
-        contract.nft_set_series_non_mintable("1".to_string());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(0)
             .build());
 
-        contract.nft_mint("1".to_string(), accounts(2));
+        contract.nft_lock(token_id, accounts(4), None);
     }
 
     #[test]
-    #[should_panic(expected = "Nearlend Dao Token series is not mintable")]
-    fn test_invalid_mint_above_copies() {
+    fn test_nft_transfer_clears_user() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, None, Some(1));
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_MINT)
             .build());
 
-        contract.nft_mint("1".to_string(), accounts(2));
-        contract.nft_mint("1".to_string(), accounts(2));
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_set_user(token_id.clone(), accounts(3), u32::MAX);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_transfer(accounts(4), token_id.clone(), None, None);
+
+        assert!(contract.nft_token_user(token_id).is_none());
     }
 
     #[test]
-    fn test_decrease_copies() {
+    #[should_panic(expected = "Nearlend Dao transfer is paused")]
+    fn test_invalid_nft_transfer_paused() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, None, Some(5));
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_MINT)
             .build());
 
-        contract.nft_mint("1".to_string(), accounts(2));
-        contract.nft_mint("1".to_string(), accounts(2));
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(1)
             .build());
 
-        contract.nft_decrease_series_copies("1".to_string(), U64::from(3));
+        contract.set_paused(PauseOperation::Transfer, true);
+        assert!(contract.get_paused().transfer);
+        assert!(!contract.get_paused().mint);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_transfer(accounts(3), token_id, None, None);
     }
 
     #[test]
-    #[should_panic(expected = "Nearlend Dao cannot decrease supply, already minted : 2")]
-    fn test_invalid_decrease_copies() {
+    fn test_nft_transfer_unsafe() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
@@ -1603,27 +6601,25 @@ mod tests {
         let mut royalty: HashMap<AccountId, u32> = HashMap::new();
         royalty.insert(accounts(1).to_string(), 1000);
 
-        create_series(&mut contract, &royalty, None, Some(5));
+        create_series(&mut contract, &royalty, None, None);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_MINT)
             .build());
 
-        contract.nft_mint("1".to_string(), accounts(2));
-        contract.nft_mint("1".to_string(), accounts(2));
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
 
-        testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(1)
-            .build());
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
 
-        contract.nft_decrease_series_copies("1".to_string(), U64::from(4));
+        contract.nft_transfer_unsafe(accounts(3), token_id.clone(), None, None);
+
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(token.owner_id, accounts(3).to_string())
     }
 
     #[test]
-    #[should_panic(expected = "Nearlend Dao not for sale")]
-    fn test_invalid_buy_price_null() {
+    fn test_nft_transfer_payout() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
@@ -1633,214 +6629,325 @@ mod tests {
         let mut royalty: HashMap<AccountId, u32> = HashMap::new();
         royalty.insert(accounts(1).to_string(), 1000);
 
-        create_series(
-            &mut contract,
-            &royalty,
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        let payout = contract.nft_transfer_payout(
+            accounts(3),
+            token_id.clone(),
+            Some(0),
             Some(U128::from(1 * 10u128.pow(24))),
-            None,
+            Some(10),
+        );
+
+        let mut payout_calc: HashMap<AccountId, U128> = HashMap::new();
+        payout_calc.insert(
+            accounts(1).to_string(),
+            U128::from((1000 * (1 * 10u128.pow(24))) / 10_000),
+        );
+        payout_calc.insert(
+            accounts(2).to_string(),
+            U128::from((9000 * (1 * 10u128.pow(24))) / 10_000),
         );
 
+        assert_eq!(payout.unwrap().payout, payout_calc);
+
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(token.owner_id, accounts(3).to_string())
+    }
+
+    #[test]
+    fn test_change_transaction_fee_immediately() {
+        let (mut context, mut contract) = setup_contract();
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(1)
             .build());
 
-        contract.nft_set_series_price("1".to_string(), None);
+        contract.set_transaction_fee(100, None);
+
+        assert_eq!(contract.get_transaction_fee().current_fee, 100);
+    }
+
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let (mut context, mut contract) = setup_contract();
 
         testing_env!(context
-            .predecessor_account_id(accounts(2))
-            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
             .build());
 
-        let token_id = contract.nft_buy("1".to_string(), accounts(2));
+        contract.propose_owner(accounts(3), None);
 
-        let token_from_nft_token = contract.nft_token(token_id);
+        let owner = contract.get_owner();
+        assert_eq!(owner.owner_id, accounts(0).to_string());
         assert_eq!(
-            token_from_nft_token.unwrap().owner_id,
-            accounts(2).to_string()
-        )
+            owner.pending_owner.unwrap().owner_id,
+            accounts(3).to_string()
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1)
+            .build());
+
+        contract.accept_ownership();
+
+        let owner = contract.get_owner();
+        assert_eq!(owner.owner_id, accounts(3).to_string());
+        assert!(owner.pending_owner.is_none());
     }
 
     #[test]
-    #[should_panic(expected = "Nearlend Dao price higher than 1000000000000000000000000000000000")]
-    fn test_invalid_price_shouldnt_be_higher_than_max_price() {
+    #[should_panic(expected = "Nearlend Dao ownership proposal expired")]
+    fn test_invalid_accept_ownership_expired() {
         let (mut context, mut contract) = setup_contract();
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        contract.propose_owner(accounts(3), Some(2_000));
 
-        create_series(
-            &mut contract,
-            &royalty,
-            Some(U128::from(1_000_000_000 * 10u128.pow(24))),
-            None,
-        );
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1)
+            .block_timestamp(2_000 * 10u64.pow(9))
+            .build());
+
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao upgrade_timelock is less than 3600")]
+    fn test_invalid_set_upgrade_timelock_below_minimum() {
+        let (mut context, mut contract) = setup_contract();
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(1)
             .build());
+
+        contract.set_upgrade_timelock(2 * MIN_UPGRADE_TIMELOCK);
+        assert_eq!(contract.get_upgrade_timelock(), 2 * MIN_UPGRADE_TIMELOCK);
+        contract.set_upgrade_timelock(0);
     }
 
     #[test]
-    fn test_nft_burn() {
+    #[should_panic(expected = "Nearlend Dao staged code is timelocked until 87400")]
+    fn test_invalid_deploy_staged_code_before_timelock() {
         let (mut context, mut contract) = setup_contract();
-        testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
-            .build());
-
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, None, None);
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(STORAGE_FOR_MINT)
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build());
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        let staged_code = contract.internal_stage_code(vec![0, 97, 115, 109]);
+        assert_eq!(
+            staged_code.activation_time,
+            1_000 + DEFAULT_UPGRADE_TIMELOCK
+        );
+        assert!(contract.get_staged_code().is_some());
 
         testing_env!(context
-            .predecessor_account_id(accounts(2))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(1)
+            .block_timestamp(2_000 * 10u64.pow(9))
             .build());
 
-        contract.nft_burn(token_id.clone());
-        let token = contract.nft_token(token_id);
-        assert!(token.is_none());
+        contract.deploy_staged_code();
     }
 
     #[test]
-    fn test_nft_transfer() {
+    fn test_deploy_staged_code() {
         let (mut context, mut contract) = setup_contract();
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        contract.internal_stage_code(vec![0, 97, 115, 109]);
 
-        create_series(&mut contract, &royalty, None, None);
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .prepaid_gas(300 * 10u64.pow(12))
+            .block_timestamp((1_000 + DEFAULT_UPGRADE_TIMELOCK as u64) * 10u64.pow(9))
+            .build());
+
+        contract.deploy_staged_code();
+        assert!(contract.get_staged_code().is_none());
+
+        let receipts: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&get_created_receipts()).unwrap()).unwrap();
+        let actions = &receipts[0]["actions"];
+        assert_eq!(receipts[0]["receiver_id"], env::current_account_id());
+        assert_eq!(
+            actions[0]["DeployContract"]["code"],
+            serde_json::json!([0, 97, 115, 109])
+        );
+        assert_eq!(actions[1]["FunctionCall"]["method_name"], "migrate");
+        assert!(actions[1]["FunctionCall"]["gas"].as_u64().unwrap() >= MIN_GAS_FOR_MIGRATE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao not enough gas to migrate")]
+    fn test_invalid_deploy_staged_code_without_gas() {
+        let (mut context, mut contract) = setup_contract();
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(STORAGE_FOR_MINT)
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build());
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        contract.internal_stage_code(vec![0, 97, 115, 109]);
 
         testing_env!(context
-            .predecessor_account_id(accounts(2))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(1)
+            .prepaid_gas(GAS_FOR_DEPLOY_STAGED_CODE)
+            .block_timestamp((1_000 + DEFAULT_UPGRADE_TIMELOCK as u64) * 10u64.pow(9))
             .build());
 
-        contract.nft_transfer(accounts(3), token_id.clone(), None, None);
-
-        let token = contract.nft_token(token_id).unwrap();
-        assert_eq!(token.owner_id, accounts(3).to_string())
+        contract.deploy_staged_code();
     }
 
     #[test]
-    fn test_nft_transfer_unsafe() {
+    fn test_fee_manager_role() {
         let (mut context, mut contract) = setup_contract();
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, None, None);
+        contract.grant_role(Role::FeeManager, accounts(2));
+        assert!(contract.has_role(Role::FeeManager, accounts(2)));
+        assert_eq!(
+            contract.get_role_members(Role::FeeManager, None, None),
+            vec![accounts(2).to_string()]
+        );
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
             .build());
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        contract.set_transaction_fee(100, None);
+        assert_eq!(contract.get_transaction_fee().current_fee, 100);
+    }
 
-        testing_env!(context.predecessor_account_id(accounts(2)).build());
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of at least 1 yoctoNEAR")]
+    fn test_invalid_grant_role_without_deposit() {
+        let (mut context, mut contract) = setup_contract();
 
-        contract.nft_transfer_unsafe(accounts(3), token_id.clone(), None, None);
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(0)
+            .build());
 
-        let token = contract.nft_token(token_id).unwrap();
-        assert_eq!(token.owner_id, accounts(3).to_string())
+        contract.grant_role(Role::FeeManager, accounts(2));
     }
 
     #[test]
-    fn test_nft_transfer_payout() {
+    #[should_panic(expected = "Requires attached deposit of at least 1 yoctoNEAR")]
+    fn test_invalid_stage_code_without_deposit() {
         let (mut context, mut contract) = setup_contract();
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(0)
             .build());
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        contract.stage_code();
+    }
 
-        create_series(&mut contract, &royalty, None, None);
+    #[test]
+    fn test_revoke_role_refunds_storage() {
+        let (mut context, mut contract) = setup_contract();
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(STORAGE_FOR_MINT)
             .build());
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        contract.storage_deposit(None, None);
 
         testing_env!(context
-            .predecessor_account_id(accounts(2))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(1)
             .build());
 
-        let payout = contract.nft_transfer_payout(
-            accounts(3),
-            token_id.clone(),
-            Some(0),
-            Some(U128::from(1 * 10u128.pow(24))),
-            Some(10),
-        );
+        contract.grant_role(Role::FeeManager, accounts(2));
+        let available = contract
+            .storage_balance_of(accounts(0))
+            .unwrap()
+            .available
+            .0;
 
-        let mut payout_calc: HashMap<AccountId, U128> = HashMap::new();
-        payout_calc.insert(
-            accounts(1).to_string(),
-            U128::from((1000 * (1 * 10u128.pow(24))) / 10_000),
-        );
-        payout_calc.insert(
-            accounts(2).to_string(),
-            U128::from((9000 * (1 * 10u128.pow(24))) / 10_000),
+        contract.revoke_role(Role::FeeManager, accounts(2));
+        assert!(!contract.has_role(Role::FeeManager, accounts(2)));
+        assert!(
+            contract
+                .storage_balance_of(accounts(0))
+                .unwrap()
+                .available
+                .0
+                > available
         );
-
-        assert_eq!(payout.unwrap().payout, payout_calc);
-
-        let token = contract.nft_token(token_id).unwrap();
-        assert_eq!(token.owner_id, accounts(3).to_string())
     }
 
     #[test]
-    fn test_change_transaction_fee_immediately() {
+    #[should_panic(expected = "Nearlend Dao fee_manager role only")]
+    fn test_invalid_change_transaction_fee_after_revoke() {
         let (mut context, mut contract) = setup_contract();
 
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.grant_role(Role::FeeManager, accounts(2));
+
         testing_env!(context
             .predecessor_account_id(accounts(0))
             .attached_deposit(1)
             .build());
 
-        contract.set_transaction_fee(100, None);
+        contract.revoke_role(Role::FeeManager, accounts(2));
 
-        assert_eq!(contract.get_transaction_fee().current_fee, 100);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.set_transaction_fee(100, None);
     }
 
     #[test]
     fn test_change_transaction_fee_with_time() {
-        let (mut context, mut contract) = setup_contract();
+        let (mut context, mut contract) = setup_contract_with_fee(500);
 
         testing_env!(context
             .predecessor_account_id(accounts(0))
@@ -1872,11 +6979,45 @@ mod tests {
         assert_eq!(contract.get_transaction_fee().current_fee, next_fee);
         assert_eq!(contract.get_transaction_fee().next_fee, None);
         assert_eq!(contract.get_transaction_fee().start_time, None);
+        assert!(matches!(
+            admin_events().as_slice(),
+            [NearlendAdminEventKind::FeeUpdate(data)]
+                if data[0].authorized_id.is_none() && data[0].old_fee == 500 && data[0].new_fee == next_fee
+        ));
+    }
+
+    #[test]
+    fn test_change_transaction_fee_cancels_schedule() {
+        let (mut context, mut contract) = setup_contract_with_fee(500);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+
+        contract.set_transaction_fee(100, Some(to_sec(1618109122863866400)));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+
+        contract.set_transaction_fee(300, None);
+        assert_eq!(contract.get_transaction_fee().current_fee, 300);
+        assert_eq!(contract.get_transaction_fee().next_fee, None);
+        assert_eq!(contract.get_transaction_fee().start_time, None);
+        assert!(matches!(
+            admin_events().as_slice(),
+            [
+                NearlendAdminEventKind::FeeScheduleCancel(cancel),
+                NearlendAdminEventKind::FeeUpdate(_)
+            ] if cancel[0].next_fee == 100
+        ));
     }
 
     #[test]
     fn test_transaction_fee_locked() {
-        let (mut context, mut contract) = setup_contract();
+        let (mut context, mut contract) = setup_contract_with_fee(500);
 
         testing_env!(context
             .predecessor_account_id(accounts(0))
@@ -1936,4 +7077,166 @@ mod tests {
         let series_transaction_fee: u128 = series.transaction_fee.unwrap().into();
         assert_eq!(series_transaction_fee, 500);
     }
+
+    #[test]
+    fn test_storage_deposit_and_withdraw() {
+        let (mut context, mut contract) = setup_contract();
+        let min_balance = contract.storage_balance_bounds().min.0;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(min_balance + STORAGE_FOR_MINT)
+            .build());
+
+        let storage_balance = contract.storage_deposit(None, None);
+        assert_eq!(storage_balance.total.0, min_balance + STORAGE_FOR_MINT);
+        assert_eq!(storage_balance.available.0, STORAGE_FOR_MINT);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        let storage_balance = contract.storage_withdraw(None);
+        assert_eq!(storage_balance.total.0, min_balance);
+        assert_eq!(storage_balance.available.0, 0);
+        assert_eq!(
+            contract.storage_balance_of(accounts(1)).unwrap().total.0,
+            min_balance
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao can't unregister an account that owns tokens")]
+    fn test_invalid_storage_unregister_with_tokens() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(contract.storage_balance_bounds().min.0)
+            .build());
+
+        contract.storage_deposit(None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.storage_unregister(None);
+    }
+
+    #[test]
+    fn test_create_series_and_mint_from_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES + STORAGE_FOR_MINT)
+            .build());
+
+        let available = contract.storage_deposit(None, None).available.0;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, None);
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        assert_eq!(
+            contract.nft_token(token_id).unwrap().owner_id,
+            accounts(2).to_string()
+        );
+        assert!(
+            contract
+                .storage_balance_of(accounts(1))
+                .unwrap()
+                .available
+                .0
+                < available
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Must attach")]
+    fn test_invalid_mint_without_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+
+        contract.nft_mint("1".to_string(), accounts(2), None);
+    }
+
+    #[test]
+    fn test_nft_approve_from_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build());
+
+        create_series(&mut contract, &HashMap::new(), None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build());
+
+        let available = contract.storage_deposit(None, None).available.0;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+
+        contract.nft_approve(token_id.clone(), accounts(3), None);
+        assert!(contract.internal_is_approved(&token_id, &accounts(3).to_string(), Some(1)));
+        let available_after_approve = contract
+            .storage_balance_of(accounts(2))
+            .unwrap()
+            .available
+            .0;
+        assert!(available_after_approve < available);
+
+        contract.nft_revoke(token_id.clone(), accounts(3));
+        assert!(!contract.internal_is_approved(&token_id, &accounts(3).to_string(), None));
+        assert!(
+            contract
+                .storage_balance_of(accounts(2))
+                .unwrap()
+                .available
+                .0
+                > available_after_approve
+        );
+    }
 }
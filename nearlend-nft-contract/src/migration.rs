@@ -0,0 +1,405 @@
+//! State layouts deployed before the current `Contract` and their migration.
+//!
+//! `ContractV1` and `ContractV2` were stored before the state was versioned, `migrate` tells them
+//! apart by layout and converts them straight to the current `Contract`, token series included.
+//! Only layouts that were actually deployed belong here: a release that changes the stored state
+//! keeps the layout of the previous release as the next `ContractVn`, converts it to the current
+//! `Contract` and bumps `STATE_VERSION`.
+
+use crate::*;
+
+/// Key near-sdk stores the contract struct under
+const STATE_KEY: &[u8] = b"STATE";
+/// States written before versioning don't have this key
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+pub const STATE_VERSION: u32 = 3;
+
+/// TokenSeries layout of the deployed contract
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenSeriesV1 {
+    pub metadata: TokenMetadata,
+    pub creator_id: AccountId,
+    pub tokens: UnorderedSet<TokenId>,
+    pub price: Option<Balance>,
+    pub is_mintable: bool,
+    pub royalty: HashMap<AccountId, u32>,
+}
+
+impl From<TokenSeriesV1> for TokenSeries {
+    fn from(prev: TokenSeriesV1) -> Self {
+        TokenSeries {
+            metadata: prev.metadata,
+            creator_id: prev.creator_id,
+            tokens: prev.tokens,
+            price: prev.price,
+            is_mintable: prev.is_mintable,
+            royalty: prev.royalty,
+            ft_token_id: None,
+            public_sale_start: None,
+            sale_start: None,
+            sale_end: None,
+            max_per_account: None,
+            price_strategy: None,
+            burned: 0,
+            reveal: None,
+            unminted_editions: None,
+            is_metadata_frozen: false,
+        }
+    }
+}
+
+/// Same prefixes and length with another value type, the stored entries are left untouched
+fn retype_token_series<Prev, Next>(
+    prev: &UnorderedMap<TokenSeriesId, Prev>,
+) -> UnorderedMap<TokenSeriesId, Next> {
+    UnorderedMap::try_from_slice(&prev.try_to_vec().unwrap()).unwrap()
+}
+
+/// Rewrites every entry in place from the `Prev` layout it was stored with to `Next`
+fn rewrite_token_series<Prev, Next>(
+    token_series_by_id: &mut UnorderedMap<TokenSeriesId, Next>,
+    into_next: fn(Prev) -> Next,
+) where
+    Prev: BorshSerialize + BorshDeserialize,
+    Next: BorshSerialize + BorshDeserialize,
+{
+    let prev: UnorderedMap<TokenSeriesId, Prev> = retype_token_series(token_series_by_id);
+    for (token_series_id, token_series) in prev.iter() {
+        // `insert` would deserialize the replaced entry as `Next`
+        token_series_by_id.insert_raw(
+            &token_series_id.try_to_vec().unwrap(),
+            &into_next(token_series).try_to_vec().unwrap(),
+        );
+    }
+}
+
+/// Original layout
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV1 {
+    pub tokens: NonFungibleToken,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeriesV1>,
+    pub treasury_id: AccountId,
+    pub transaction_fee: TransactionFee,
+}
+
+/// V1 with per series market data transaction fee
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV2 {
+    pub tokens: NonFungibleToken,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeriesV1>,
+    pub treasury_id: AccountId,
+    pub transaction_fee: TransactionFee,
+    pub market_data_transaction_fee: MarketDataTransactionFee,
+}
+
+impl From<ContractV1> for ContractV2 {
+    fn from(prev: ContractV1) -> Self {
+        ContractV2 {
+            tokens: prev.tokens,
+            metadata: prev.metadata,
+            token_series_by_id: prev.token_series_by_id,
+            treasury_id: prev.treasury_id,
+            transaction_fee: prev.transaction_fee,
+            market_data_transaction_fee: MarketDataTransactionFee {
+                transaction_fee: UnorderedMap::new(StorageKey::MarketDataTransactionFee),
+            },
+        }
+    }
+}
+
+/// The token series entries keep the V1 layout until `into_current` rewrites them
+impl From<ContractV2> for Contract {
+    fn from(prev: ContractV2) -> Self {
+        Contract {
+            tokens: prev.tokens,
+            metadata: prev.metadata,
+            token_series_by_id: retype_token_series(&prev.token_series_by_id),
+            treasury_id: prev.treasury_id,
+            transaction_fee: prev.transaction_fee,
+            market_data_transaction_fee: prev.market_data_transaction_fee,
+            presale_allowlist: LookupMap::new(StorageKey::PresaleAllowlist),
+            minted_per_account: LookupMap::new(StorageKey::MintedPerAccount),
+            auctions: UnorderedMap::new(StorageKey::Auctions),
+            token_locks: LookupMap::new(StorageKey::TokenLocks),
+            token_users: LookupMap::new(StorageKey::TokenUsers),
+            roles: LookupMap::new(StorageKey::Roles),
+            paused: PauseState::default(),
+            pending_owner: None,
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            staged_code_info: None,
+            upgrade_timelock: DEFAULT_UPGRADE_TIMELOCK,
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            edition_metadata: LookupMap::new(StorageKey::EditionMetadata),
+            ft_payouts: LookupMap::new(StorageKey::FtPayouts),
+            storage_payers: LookupMap::new(StorageKey::StoragePayers),
+        }
+    }
+}
+
+// only lives for the duration of `migrate`
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedContract {
+    V1(ContractV1),
+    V2(ContractV2),
+    V3(Contract),
+}
+
+impl VersionedContract {
+    pub fn read() -> Self {
+        let state = env::storage_read(STATE_KEY).expect("ERR_NOT_INITIALIZED");
+        let version = env::storage_read(STATE_VERSION_KEY)
+            .map(|version| u32::try_from_slice(&version).expect("ERR_STATE_VERSION"));
+        VersionedContract::from_slice(&state, version)
+    }
+
+    pub fn from_slice(state: &[u8], version: Option<u32>) -> Self {
+        match version {
+            Some(STATE_VERSION) => {
+                VersionedContract::V3(Contract::try_from_slice(state).expect("ERR_STATE"))
+            }
+            Some(version) => {
+                env::panic(format!("ERR_UNKNOWN_STATE_VERSION {}", version).as_bytes())
+            }
+            // unversioned states only differ by layout, try_from_slice fails unless every byte is read
+            None => match ContractV2::try_from_slice(state) {
+                Ok(prev) => VersionedContract::V2(prev),
+                Err(_) => {
+                    VersionedContract::V1(ContractV1::try_from_slice(state).expect("ERR_STATE"))
+                }
+            },
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        match self {
+            VersionedContract::V1(_) => 1,
+            VersionedContract::V2(_) => 2,
+            VersionedContract::V3(_) => 3,
+        }
+    }
+
+    /// Converts the stored layout to the current one
+    pub fn into_current(self) -> Contract {
+        match self {
+            VersionedContract::V1(prev) => {
+                VersionedContract::V2(ContractV2::from(prev)).into_current()
+            }
+            VersionedContract::V2(prev) => {
+                let mut contract = Contract::from(prev);
+                rewrite_token_series::<TokenSeriesV1, _>(
+                    &mut contract.token_series_by_id,
+                    TokenSeries::from,
+                );
+                contract
+            }
+            VersionedContract::V3(contract) => contract,
+        }
+    }
+}
+
+pub fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.try_to_vec().unwrap());
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    fn setup_context() -> VMContextBuilder {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        context
+    }
+
+    fn contract_v1() -> ContractV1 {
+        let mut token_series_by_id = UnorderedMap::new(StorageKey::TokenSeriesById);
+        token_series_by_id.insert(
+            &"1".to_string(),
+            &TokenSeriesV1 {
+                metadata: TokenMetadata {
+                    title: Some("Tsundere land".to_string()),
+                    description: None,
+                    media: None,
+                    media_hash: None,
+                    copies: Some(10),
+                    issued_at: None,
+                    expires_at: None,
+                    starts_at: None,
+                    updated_at: None,
+                    extra: None,
+                    reference: None,
+                    reference_hash: None,
+                },
+                creator_id: accounts(1).to_string(),
+                tokens: UnorderedSet::new(StorageKey::TokensBySeriesInner {
+                    token_series: "1".to_string(),
+                }),
+                price: Some(1_000),
+                is_mintable: true,
+                royalty: HashMap::new(),
+            },
+        );
+
+        ContractV1 {
+            tokens: NonFungibleToken::new(
+                StorageKey::NonFungibleToken,
+                accounts(0),
+                Some(StorageKey::TokenMetadata),
+                Some(StorageKey::Enumeration),
+                Some(StorageKey::Approval),
+            ),
+            metadata: LazyOption::new(StorageKey::Metadata, None),
+            token_series_by_id,
+            treasury_id: accounts(4).to_string(),
+            transaction_fee: TransactionFee {
+                next_fee: None,
+                start_time: None,
+                current_fee: 500,
+            },
+        }
+    }
+
+    fn assert_migrated(contract: &Contract) {
+        assert_eq!(contract.tokens.owner_id, accounts(0).to_string());
+        assert_eq!(contract.treasury_id, accounts(4).to_string());
+
+        let token_series = contract.token_series_by_id.get(&"1".to_string()).unwrap();
+        assert_eq!(contract.token_series_by_id.len(), 1);
+        assert_eq!(token_series.creator_id, accounts(1).to_string());
+        assert_eq!(token_series.price, Some(1_000));
+        assert!(token_series.ft_token_id.is_none());
+        assert!(token_series.price_strategy.is_none());
+        assert_eq!(token_series.burned, 0);
+        assert!(token_series.reveal.is_none());
+        assert!(token_series.unminted_editions.is_none());
+        assert!(!token_series.is_metadata_frozen);
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        setup_context();
+        let state = contract_v1().try_to_vec().unwrap();
+
+        let versioned = VersionedContract::from_slice(&state, None);
+        assert_eq!(versioned.version(), 1);
+        assert_migrated(&versioned.into_current());
+    }
+
+    #[test]
+    fn test_migrate_v2() {
+        setup_context();
+        let prev: ContractV2 = contract_v1().into();
+        let state = prev.try_to_vec().unwrap();
+
+        let versioned = VersionedContract::from_slice(&state, None);
+        assert_eq!(versioned.version(), 2);
+        assert_migrated(&versioned.into_current());
+    }
+
+    #[test]
+    fn test_migrate_v3() {
+        setup_context();
+        let state = VersionedContract::V1(contract_v1())
+            .into_current()
+            .try_to_vec()
+            .unwrap();
+
+        let versioned = VersionedContract::from_slice(&state, Some(STATE_VERSION));
+        assert_eq!(versioned.version(), 3);
+        assert_migrated(&versioned.into_current());
+    }
+
+    #[test]
+    fn test_migrate_deployed_state_keeps_token_series() {
+        setup_context();
+        let mut prev: ContractV2 = contract_v1().into();
+        let mut token_series = prev.token_series_by_id.get(&"1".to_string()).unwrap();
+        token_series.price = None;
+        prev.token_series_by_id
+            .insert(&"2".to_string(), &token_series);
+        prev.market_data_transaction_fee
+            .transaction_fee
+            .insert(&"1".to_string(), &300);
+        env::state_write(&prev);
+
+        let mut contract = Contract::migrate();
+        assert_eq!(contract.token_series_by_id.len(), 2);
+        assert_eq!(
+            contract
+                .token_series_by_id
+                .get(&"1".to_string())
+                .unwrap()
+                .price,
+            Some(1_000)
+        );
+        assert_eq!(
+            contract
+                .token_series_by_id
+                .get(&"2".to_string())
+                .unwrap()
+                .price,
+            None
+        );
+        assert_eq!(
+            contract
+                .market_data_transaction_fee
+                .transaction_fee
+                .get(&"1".to_string()),
+            Some(300)
+        );
+
+        let token_series = contract.token_series_by_id.get(&"1".to_string()).unwrap();
+        contract
+            .token_series_by_id
+            .insert(&"3".to_string(), &token_series);
+        assert_eq!(
+            contract.token_series_by_id.keys_as_vector().to_vec(),
+            vec!["1".to_string(), "2".to_string(), "3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_migrate_by_upgrader() {
+        let mut context = setup_context();
+        env::state_write(&contract_v1());
+        let mut contract = Contract::migrate();
+
+        let mut members = UnorderedSet::new(StorageKey::RoleMembers {
+            role: Role::Upgrader,
+        });
+        members.insert(&accounts(2).to_string());
+        contract.roles.insert(&Role::Upgrader, &members);
+        env::state_write(&contract);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        assert_migrated(&Contract::migrate());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nearlend Dao upgrader role only")]
+    fn test_invalid_migrate_without_role() {
+        let mut context = setup_context();
+        env::state_write(&contract_v1());
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        Contract::migrate();
+    }
+
+    #[test]
+    fn test_migrate_from_storage() {
+        setup_context();
+        env::state_write(&contract_v1());
+        assert_eq!(VersionedContract::read().version(), 1);
+
+        let contract = Contract::migrate();
+        assert_migrated(&contract);
+
+        env::state_write(&contract);
+        assert_eq!(VersionedContract::read().version(), STATE_VERSION);
+    }
+}
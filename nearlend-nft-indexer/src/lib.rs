@@ -0,0 +1,209 @@
+//! Rebuilds the Nearlend NFT contract state from receipt logs, using the event types from the
+//! contract crate so the parser follows every change to the log shape.
+
+pub mod log;
+pub mod projection;
+
+use serde::Deserialize;
+
+pub use log::{parse_log, LegacyLog, LogError, ParsedLog};
+pub use projection::Projection;
+
+/// One JSON line of input: a receipt with its `logs`, or a single log string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LogLine {
+    Receipt { logs: Vec<String> },
+    Log(String),
+}
+
+/// Receipt logs in a JSON line, a line that isn't JSON is taken as the log itself
+pub fn logs_from_line(line: &str) -> Vec<String> {
+    match serde_json::from_str(line) {
+        Ok(LogLine::Receipt { logs }) => logs,
+        Ok(LogLine::Log(log)) => vec![log],
+        Err(_) => vec![line.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nearlend_nft_contract::event::{
+        AuctionBidData, AuctionCreateData, AuctionSettleData, NearlendSeriesEventKind, NftMintData,
+        NftTransferData, SeriesSetPriceStrategyData,
+    };
+    use nearlend_nft_contract::{NearEvent, PriceStrategy};
+
+    const LEGACY_CREATE_SERIES: &str = r#"{"type":"nft_create_series","params":{"token_series_id":"1","token_metadata":{"title":"Tsundere land","description":null,"media":null,"media_hash":null,"copies":10,"issued_at":null,"expires_at":null,"starts_at":null,"updated_at":null,"extra":null,"reference":null,"reference_hash":null},"creator_id":"alice.near","price":"1000000000000000000000000","royalty":{},"transaction_fee":"500"}}"#;
+
+    #[test]
+    fn test_legacy_series_logs() {
+        let mut projection = Projection::default();
+        projection.apply_log(LEGACY_CREATE_SERIES).unwrap();
+        projection
+            .apply_log(r#"{"type":"nft_decrease_series_copies","params":{"token_series_id":"1","copies":"4","is_non_mintable":true}}"#)
+            .unwrap();
+
+        let series = &projection.series["1"];
+        assert_eq!(series.creator_id, "alice.near");
+        assert_eq!(series.title.as_deref(), Some("Tsundere land"));
+        assert_eq!(series.copies, Some(4));
+        assert!(!series.is_mintable);
+
+        match parse_log(r#"{"type":"nft_set_series_sale_window","params":{"token_series_id":"1"}}"#)
+        {
+            Ok(ParsedLog::Legacy(legacy)) => {
+                assert_eq!(legacy.log_type, "nft_set_series_sale_window")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sales_and_owners() {
+        let mut projection = Projection::default();
+        projection.apply_log(LEGACY_CREATE_SERIES).unwrap();
+
+        let mint = NearEvent::nft_mint(vec![NftMintData {
+            owner_id: "bob.near".to_string(),
+            token_ids: vec!["1:1".to_string()],
+            memo: Some(r#"{"price":"1000000000000000000000000"}"#.to_string()),
+        }]);
+        let transfer = NearEvent::nft_transfer(vec![NftTransferData {
+            authorized_id: None,
+            old_owner_id: "bob.near".to_string(),
+            new_owner_id: "carol.near".to_string(),
+            token_ids: vec!["1:1".to_string()],
+            memo: None,
+        }]);
+        let receipt = serde_json::json!({ "logs": [mint.to_string(), transfer.to_string()] });
+        for log in logs_from_line(&receipt.to_string()) {
+            projection.apply_log(&log).unwrap();
+        }
+
+        assert_eq!(projection.owners["1:1"], "carol.near");
+        assert_eq!(projection.series["1"].minted, 1);
+        assert_eq!(projection.sales.len(), 1);
+        assert_eq!(projection.sales[0].buyer_id, "bob.near");
+        assert_eq!(projection.sales[0].price, "1000000000000000000000000");
+        assert_eq!(projection.sales[0].transaction_fee.as_deref(), Some("500"));
+    }
+
+    #[test]
+    fn test_price_strategy_and_batch_mints() {
+        let mut projection = Projection::default();
+        projection.apply_log(LEGACY_CREATE_SERIES).unwrap();
+
+        let set_price_strategy = NearEvent::new_nearlend_series_v1(
+            NearlendSeriesEventKind::SeriesSetPriceStrategy(vec![SeriesSetPriceStrategyData {
+                token_series_id: "1".to_string(),
+                price_strategy: serde_json::from_str(
+                    r#"{"type":"linear_curve","base_price":"1000","increment":"100"}"#,
+                )
+                .unwrap(),
+                transaction_fee: Some("300".to_string()),
+            }]),
+        );
+        let buy = NearEvent::nft_mint(vec![NftMintData {
+            owner_id: "bob.near".to_string(),
+            token_ids: vec!["1:1".to_string()],
+            memo: Some(r#"{"price":"1000"}"#.to_string()),
+        }]);
+        let batch_mint = NearEvent::nft_mint(vec![
+            NftMintData {
+                owner_id: "carol.near".to_string(),
+                token_ids: vec!["1:2".to_string(), "1:3".to_string()],
+                memo: None,
+            },
+            NftMintData {
+                owner_id: "dave.near".to_string(),
+                token_ids: vec!["1:4".to_string()],
+                memo: None,
+            },
+        ]);
+        for event in &[set_price_strategy, buy, batch_mint] {
+            projection.apply_log(&event.to_string()).unwrap();
+        }
+
+        let series = &projection.series["1"];
+        assert!(matches!(
+            series.price_strategy,
+            Some(PriceStrategy::LinearCurve { .. })
+        ));
+        assert_eq!(series.minted, 4);
+        assert_eq!(projection.owners["1:3"], "carol.near");
+        assert_eq!(projection.owners["1:4"], "dave.near");
+        assert_eq!(projection.sales.len(), 1);
+        assert_eq!(projection.sales[0].price, "1000");
+        assert_eq!(projection.sales[0].transaction_fee.as_deref(), Some("300"));
+    }
+
+    #[test]
+    fn test_auction_sale() {
+        let mut projection = Projection::default();
+        projection.apply_log(LEGACY_CREATE_SERIES).unwrap();
+
+        let events = vec![
+            NearEvent::new_nearlend_series_v1(NearlendSeriesEventKind::AuctionCreate(vec![
+                AuctionCreateData {
+                    token_series_id: "1".to_string(),
+                    reserve_price: "1000".to_string(),
+                    min_bid_increment: "100".to_string(),
+                    end_time: 1_000,
+                    extension: 300,
+                },
+            ])),
+            NearEvent::new_nearlend_series_v1(NearlendSeriesEventKind::AuctionBid(vec![
+                AuctionBidData {
+                    token_series_id: "1".to_string(),
+                    bidder_id: "bob.near".to_string(),
+                    bid: "1200".to_string(),
+                    end_time: 1_300,
+                },
+            ])),
+        ];
+        for event in &events {
+            projection.apply_log(&event.to_string()).unwrap();
+        }
+        let auction = projection.series["1"].auction.as_ref().unwrap();
+        assert_eq!(auction.bidder_id.as_deref(), Some("bob.near"));
+        assert_eq!(auction.end_time, 1_300);
+
+        let settle = vec![
+            NearEvent::nft_mint(vec![NftMintData {
+                owner_id: "bob.near".to_string(),
+                token_ids: vec!["1:1".to_string()],
+                memo: Some(r#"{"price":"1200"}"#.to_string()),
+            }]),
+            NearEvent::new_nearlend_series_v1(NearlendSeriesEventKind::AuctionSettle(vec![
+                AuctionSettleData {
+                    token_series_id: "1".to_string(),
+                    token_id: Some("1:1".to_string()),
+                },
+            ])),
+        ];
+        for event in &settle {
+            projection.apply_log(&event.to_string()).unwrap();
+        }
+
+        assert!(projection.series["1"].auction.is_none());
+        assert_eq!(projection.owners["1:1"], "bob.near");
+        assert_eq!(projection.sales.len(), 1);
+        assert_eq!(projection.sales[0].price, "1200");
+        assert!(projection.sales[0].is_auction);
+    }
+
+    #[test]
+    fn test_invalid_event() {
+        let mut projection = Projection::default();
+        assert!(matches!(
+            projection.apply_log("Transfer 1:1 from bob.near to carol.near"),
+            Err(LogError::NotAnEvent)
+        ));
+        assert!(matches!(
+            projection.apply_log(r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner":"bob.near"}]}"#),
+            Err(LogError::Invalid(_))
+        ));
+    }
+}
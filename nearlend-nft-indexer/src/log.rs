@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+use nearlend_nft_contract::event::{NearlendSeriesEventKind, NearlendSeriesEventKind::*};
+use nearlend_nft_contract::NearEvent;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+pub const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
+/// `{"type": ..., "params": ...}` log written by the contract before NEP-297 events
+#[derive(Deserialize, Debug)]
+pub struct LegacyLog {
+    #[serde(rename = "type")]
+    pub log_type: String,
+    pub params: Value,
+}
+
+#[derive(Debug)]
+pub enum ParsedLog {
+    Event(NearEvent),
+    /// `{"type": ..., "params": ...}` log of a type the contract never logged before events
+    Legacy(LegacyLog),
+}
+
+#[derive(Debug)]
+pub enum LogError {
+    /// plain text log, not emitted by the contract as an event
+    NotAnEvent,
+    /// looks like an event but doesn't match the shape in `event.rs`
+    Invalid(serde_json::Error),
+}
+
+impl Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogError::NotAnEvent => f.write_str("not an event"),
+            LogError::Invalid(err) => write!(f, "invalid event: {}", err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for LogError {
+    fn from(err: serde_json::Error) -> Self {
+        LogError::Invalid(err)
+    }
+}
+
+/// Parses a single receipt log, the legacy logs of `nft_create_series`, `nft_set_series_price`,
+/// `nft_set_series_non_mintable` and `nft_decrease_series_copies` are converted to their
+/// `nearlend_series` event
+pub fn parse_log(log: &str) -> Result<ParsedLog, LogError> {
+    if let Some(event_json) = log.strip_prefix(EVENT_JSON_PREFIX) {
+        return Ok(ParsedLog::Event(serde_json::from_str(event_json)?));
+    }
+
+    let legacy: LegacyLog = serde_json::from_str(log).map_err(|_| LogError::NotAnEvent)?;
+    Ok(match legacy_series_event_kind(&legacy)? {
+        Some(event_kind) => ParsedLog::Event(NearEvent::new_nearlend_series_v1(event_kind)),
+        None => ParsedLog::Legacy(legacy),
+    })
+}
+
+fn legacy_series_event_kind(
+    legacy: &LegacyLog,
+) -> Result<Option<NearlendSeriesEventKind>, serde_json::Error> {
+    let params = legacy.params.clone();
+    let event_kind = match legacy.log_type.as_str() {
+        "nft_create_series" => SeriesCreate(from_params(params)?),
+        "nft_set_series_price" => SeriesSetPrice(from_params(params)?),
+        "nft_set_series_non_mintable" => SeriesSetNonMintable(from_params(params)?),
+        "nft_decrease_series_copies" => {
+            SeriesDecreaseCopies(from_params(copies_as_number(params))?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(event_kind))
+}
+
+fn from_params<T: DeserializeOwned>(params: Value) -> Result<Vec<T>, serde_json::Error> {
+    Ok(vec![serde_json::from_value(params)?])
+}
+
+/// legacy `nft_decrease_series_copies` logged copies as a U64 string
+fn copies_as_number(mut params: Value) -> Value {
+    if let Some(copies) = params
+        .get("copies")
+        .and_then(Value::as_str)
+        .and_then(|copies| copies.parse::<u64>().ok())
+    {
+        params["copies"] = Value::from(copies);
+    }
+    params
+}
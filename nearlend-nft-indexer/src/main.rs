@@ -0,0 +1,40 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+
+use nearlend_nft_indexer::{logs_from_line, LogError, Projection};
+
+/// Reads JSON lines of receipt logs from the file given as argument, or stdin,
+/// and prints the projection as JSON
+fn main() {
+    let input: Box<dyn BufRead> = match env::args().nth(1) {
+        Some(path) => match File::open(&path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("cannot open {}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+
+    let mut projection = Projection::default();
+    for (line_number, line) in input.lines().enumerate() {
+        let line = line.unwrap_or_else(|err| {
+            eprintln!("cannot read line {}: {}", line_number + 1, err);
+            process::exit(1);
+        });
+        if line.trim().is_empty() {
+            continue;
+        }
+        for log in logs_from_line(&line) {
+            match projection.apply_log(&log) {
+                Ok(()) | Err(LogError::NotAnEvent) => {}
+                Err(err) => eprintln!("line {}: {}: {}", line_number + 1, err, log),
+            }
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&projection).unwrap());
+}
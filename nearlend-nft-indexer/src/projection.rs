@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use nearlend_nft_contract::event::{
+    NearlendAdminEventKind, NearlendSeriesEventKind, Nep171EventKind, NftMintData,
+};
+use nearlend_nft_contract::{NearEvent, PriceStrategy, TOKEN_DELIMETER};
+use serde::{Deserialize, Serialize};
+
+use crate::log::{parse_log, LogError, ParsedLog};
+
+#[derive(Serialize, Default, Debug)]
+pub struct SeriesView {
+    pub creator_id: String,
+    pub title: Option<String>,
+    pub copies: Option<u64>,
+    pub price: Option<String>,
+    /// takes precedence over `price` while set
+    pub price_strategy: Option<PriceStrategy>,
+    pub ft_token_id: Option<String>,
+    pub transaction_fee: String,
+    pub is_mintable: bool,
+    pub minted: u64,
+    pub burned: u64,
+    /// running auction, cleared once settled or cancelled
+    pub auction: Option<AuctionView>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuctionView {
+    pub reserve_price: String,
+    pub min_bid_increment: String,
+    pub end_time: u32,
+    pub bidder_id: Option<String>,
+    pub bid: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Sale {
+    pub token_id: String,
+    pub token_series_id: String,
+    pub buyer_id: String,
+    pub price: String,
+    /// `None` when paid in NEAR
+    pub ft_token_id: Option<String>,
+    /// series transaction fee in basis points at the time of the sale
+    pub transaction_fee: Option<String>,
+    /// won with `nft_settle_auction`, `price` is the winning bid
+    pub is_auction: bool,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct Fees {
+    pub current_fee: Option<u16>,
+    pub next_fee: Option<u16>,
+    pub start_time: Option<u32>,
+    pub treasury_id: Option<String>,
+}
+
+/// Price memo attached to mints paid by `nft_buy`, `ft_on_transfer` and `nft_settle_auction`,
+/// mints without it (`nft_mint`, `nft_mint_batch`, ...) are not sales
+#[derive(Deserialize)]
+struct SaleMemo {
+    price: String,
+    ft_token_id: Option<String>,
+}
+
+/// State rebuilt from the contract logs: series, token owners, sales and fees
+#[derive(Serialize, Default, Debug)]
+pub struct Projection {
+    pub series: BTreeMap<String, SeriesView>,
+    pub owners: BTreeMap<String, String>,
+    pub sales: Vec<Sale>,
+    pub fees: Fees,
+}
+
+impl Projection {
+    pub fn apply_log(&mut self, log: &str) -> Result<(), LogError> {
+        if let ParsedLog::Event(event) = parse_log(log)? {
+            self.apply(&event);
+        }
+        Ok(())
+    }
+
+    pub fn apply(&mut self, event: &NearEvent) {
+        match event {
+            NearEvent::Nep171(event) => self.apply_nep171(&event.event_kind),
+            NearEvent::NearlendSeries(event) => self.apply_series(&event.event_kind),
+            NearEvent::NearlendAdmin(event) => self.apply_admin(&event.event_kind),
+            NearEvent::NearlendToken(_) => {}
+        }
+    }
+
+    fn apply_nep171(&mut self, event_kind: &Nep171EventKind) {
+        match event_kind {
+            Nep171EventKind::NftMint(data) => data.iter().for_each(|mint| self.apply_mint(mint)),
+            Nep171EventKind::NftTransfer(data) => {
+                for transfer in data {
+                    for token_id in &transfer.token_ids {
+                        self.owners
+                            .insert(token_id.clone(), transfer.new_owner_id.clone());
+                    }
+                }
+            }
+            Nep171EventKind::NftBurn(data) => {
+                for token_id in data.iter().flat_map(|burn| &burn.token_ids) {
+                    self.owners.remove(token_id);
+                    if let Some(series) = self.series.get_mut(token_series_id(token_id)) {
+                        series.burned += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn apply_mint(&mut self, mint: &NftMintData) {
+        let sale_memo = mint
+            .memo
+            .as_ref()
+            .and_then(|memo| serde_json::from_str::<SaleMemo>(memo).ok());
+
+        for token_id in &mint.token_ids {
+            self.owners.insert(token_id.clone(), mint.owner_id.clone());
+            let token_series_id = token_series_id(token_id);
+            let series = self.series.get_mut(token_series_id);
+            let (transaction_fee, is_auction) = match series {
+                Some(series) => {
+                    series.minted += 1;
+                    // other mints are rejected while the series is on auction
+                    (
+                        Some(series.transaction_fee.clone()),
+                        series.auction.is_some(),
+                    )
+                }
+                None => (None, false),
+            };
+            if let Some(sale_memo) = &sale_memo {
+                self.sales.push(Sale {
+                    token_id: token_id.clone(),
+                    token_series_id: token_series_id.to_string(),
+                    buyer_id: mint.owner_id.clone(),
+                    price: sale_memo.price.clone(),
+                    ft_token_id: sale_memo.ft_token_id.clone(),
+                    transaction_fee,
+                    is_auction,
+                });
+            }
+        }
+    }
+
+    fn apply_series(&mut self, event_kind: &NearlendSeriesEventKind) {
+        match event_kind {
+            NearlendSeriesEventKind::SeriesCreate(data) => {
+                for create in data {
+                    self.series.insert(
+                        create.token_series_id.clone(),
+                        SeriesView {
+                            creator_id: create.creator_id.clone(),
+                            title: create.token_metadata.title.clone(),
+                            copies: create.token_metadata.copies,
+                            price: create.price.clone(),
+                            ft_token_id: create.ft_token_id.clone(),
+                            transaction_fee: create.transaction_fee.clone(),
+                            is_mintable: true,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+            NearlendSeriesEventKind::SeriesSetPrice(data) => {
+                for set_price in data {
+                    if let Some(series) = self.series.get_mut(&set_price.token_series_id) {
+                        series.price = set_price.price.clone();
+                        series.transaction_fee = set_price.transaction_fee.clone();
+                    }
+                }
+            }
+            NearlendSeriesEventKind::SeriesSetPriceStrategy(data) => {
+                for set_price_strategy in data {
+                    if let Some(series) = self.series.get_mut(&set_price_strategy.token_series_id) {
+                        series.price_strategy = set_price_strategy.price_strategy.clone();
+                        if let Some(transaction_fee) = &set_price_strategy.transaction_fee {
+                            series.transaction_fee = transaction_fee.clone();
+                        }
+                    }
+                }
+            }
+            NearlendSeriesEventKind::SeriesSetNonMintable(data) => {
+                for set_non_mintable in data {
+                    if let Some(series) = self.series.get_mut(&set_non_mintable.token_series_id) {
+                        series.is_mintable = false;
+                    }
+                }
+            }
+            NearlendSeriesEventKind::SeriesDecreaseCopies(data) => {
+                for decrease in data {
+                    if let Some(series) = self.series.get_mut(&decrease.token_series_id) {
+                        series.copies = Some(decrease.copies);
+                        series.is_mintable &= !decrease.is_non_mintable;
+                    }
+                }
+            }
+            NearlendSeriesEventKind::AuctionCreate(data) => {
+                for create in data {
+                    if let Some(series) = self.series.get_mut(&create.token_series_id) {
+                        series.auction = Some(AuctionView {
+                            reserve_price: create.reserve_price.clone(),
+                            min_bid_increment: create.min_bid_increment.clone(),
+                            end_time: create.end_time,
+                            bidder_id: None,
+                            bid: None,
+                        });
+                    }
+                }
+            }
+            NearlendSeriesEventKind::AuctionBid(data) => {
+                for bid in data {
+                    let auction = self
+                        .series
+                        .get_mut(&bid.token_series_id)
+                        .and_then(|series| series.auction.as_mut());
+                    if let Some(auction) = auction {
+                        auction.bidder_id = Some(bid.bidder_id.clone());
+                        auction.bid = Some(bid.bid.clone());
+                        auction.end_time = bid.end_time;
+                    }
+                }
+            }
+            NearlendSeriesEventKind::AuctionSettle(data) => {
+                for settle in data {
+                    if let Some(series) = self.series.get_mut(&settle.token_series_id) {
+                        series.auction = None;
+                    }
+                }
+            }
+            NearlendSeriesEventKind::AuctionCancel(data) => {
+                for cancel in data {
+                    if let Some(series) = self.series.get_mut(&cancel.token_series_id) {
+                        series.auction = None;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_admin(&mut self, event_kind: &NearlendAdminEventKind) {
+        match event_kind {
+            NearlendAdminEventKind::FeeSchedule(data) => {
+                for fee_schedule in data {
+                    self.fees.current_fee = Some(fee_schedule.current_fee);
+                    self.fees.next_fee = Some(fee_schedule.next_fee);
+                    self.fees.start_time = Some(fee_schedule.start_time);
+                }
+            }
+            NearlendAdminEventKind::FeeScheduleCancel(data) => {
+                if !data.is_empty() {
+                    self.fees.next_fee = None;
+                    self.fees.start_time = None;
+                }
+            }
+            NearlendAdminEventKind::FeeUpdate(data) => {
+                for fee_update in data {
+                    self.fees.current_fee = Some(fee_update.new_fee);
+                    self.fees.next_fee = None;
+                    self.fees.start_time = None;
+                }
+            }
+            NearlendAdminEventKind::TreasuryUpdate(data) => {
+                for treasury_update in data {
+                    self.fees.treasury_id = Some(treasury_update.new_treasury_id.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn token_series_id(token_id: &str) -> &str {
+    token_id.split(TOKEN_DELIMETER).next().unwrap_or(token_id)
+}
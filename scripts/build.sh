@@ -1,5 +1,5 @@
 #!/bin/bash
 set -e
 cd "`dirname $0`"
-RUSTFLAGS='-C link-arg=-s' cargo build --all --target wasm32-unknown-unknown --release
-cp ../target/wasm32-unknown-unknown/release/*.wasm ../out/main.wasm
+RUSTFLAGS='-C link-arg=-s' cargo build -p nearlend-nft-contract --target wasm32-unknown-unknown --release
+cp ../target/wasm32-unknown-unknown/release/nearlend_nft_contract.wasm ../out/main.wasm